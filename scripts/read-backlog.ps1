@@ -50,6 +50,8 @@ function Read-FileBacklog {
         totalIssues     = 0
         completedIssues = 0
         currentIssue    = ""
+        nextIssue       = ""
+        recentCompleted = @()
         backlogPath     = $FilePath
         error           = $null
     }
@@ -77,14 +79,19 @@ function Read-FileBacklog {
         $result.totalIssues = $total
         $result.completedIssues = $totalChecked
         
-        # Find first unchecked item text
+        # Find the first two unchecked items (current and next issue)
         $lines = $content -split "`n"
-        foreach ($line in $lines) {
-            if ($line -match '[-*]\s\[ \]\s*(.+)') {
-                $result.currentIssue = $matches[1].Trim()
-                break
-            }
-        }
+        $pending = @($lines | ForEach-Object {
+                if ($_ -match '[-*]\s\[ \]\s*(.+)') { $matches[1].Trim() }
+            } | Select-Object -First 2)
+        if ($pending.Count -gt 0) { $result.currentIssue = $pending[0] }
+        if ($pending.Count -gt 1) { $result.nextIssue = $pending[1] }
+
+        # Last checked items, for carry-over summaries
+        $done = @($lines | Where-Object { $_ -match '[-*]\s\[x\]\s*(.+)' } | ForEach-Object {
+                ($_ -replace '^\s*[-*]\s\[x\]\s*', '').Trim()
+            })
+        $result.recentCompleted = @($done | Select-Object -Last 5)
         if (-not $result.currentIssue -and $totalUnchecked -eq 0) {
             $result.currentIssue = "DONE"
         }
@@ -96,17 +103,23 @@ function Read-FileBacklog {
             $result.totalIssues = $sections.Count - 1  # First split is before first header
             $completed = 0
             $firstIncomplete = $null
+            $done = @()
             for ($i = 1; $i -lt $sections.Count; $i++) {
                 if (Test-IsCompleted $sections[$i]) {
                     $completed++
+                    $done += ($sections[$i] -split "`n")[0].Trim()
                 }
                 elseif ($null -eq $firstIncomplete) {
                     $headerLine = ($sections[$i] -split "`n")[0].Trim()
                     $firstIncomplete = $headerLine
                 }
+                elseif (-not $result.nextIssue) {
+                    $result.nextIssue = ($sections[$i] -split "`n")[0].Trim()
+                }
             }
             $result.completedIssues = $completed
             $result.currentIssue = if ($firstIncomplete) { $firstIncomplete } else { "DONE" }
+            $result.recentCompleted = @($done | Select-Object -Last 5)
         }
         else {
             $result.error = "No checkboxes or sections found in file"
@@ -124,6 +137,8 @@ function Read-FolderBacklog {
         totalIssues     = 0
         completedIssues = 0
         currentIssue    = ""
+        nextIssue       = ""
+        recentCompleted = @()
         backlogPath     = $FolderPath
         error           = $null
     }
@@ -143,12 +158,14 @@ function Read-FolderBacklog {
 
     $completed = 0
     $firstIncomplete = $null
+    $done = @()
     
     foreach ($file in $issueFiles | Sort-Object Name) {
         $content = Get-Content $file.FullName -Raw
         
         if (Test-IsCompleted $content) {
             $completed++
+            $done += $file.BaseName
         }
         elseif ($null -eq $firstIncomplete) {
             # Extract ID from filename (APP-001, ISSUE-1, etc.) or use filename
@@ -159,10 +176,14 @@ function Read-FolderBacklog {
                 $firstIncomplete = $file.BaseName
             }
         }
+        elseif (-not $result.nextIssue) {
+            $result.nextIssue = $file.BaseName
+        }
     }
     
     $result.completedIssues = $completed
     $result.currentIssue = if ($firstIncomplete) { $firstIncomplete } else { "DONE" }
+    $result.recentCompleted = @($done | Select-Object -Last 5)
     
     return $result
 }
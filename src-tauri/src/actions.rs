@@ -0,0 +1,918 @@
+// Editor actions: click, accept dialog, scroll, write to chat
+// Each action is an input sequence over an InputBackend, using the chords of
+// the instance's keybinding profile, and reports an ActionOutcome describing
+// how far it got
+
+use serde::Serialize;
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+
+use crate::carryover;
+use crate::config::ConfigStore;
+use crate::detect::ElementKind;
+use crate::idle::{self, SystemIdle};
+use crate::input::{self, DryRunInput, InputAction, InputBackend};
+use crate::keymap::{EditorAction, KeybindingProfile};
+use crate::layout::LayoutProfile;
+use crate::notify;
+use crate::runtime::Runtime;
+use crate::scheduler::{ActionToken, GuardedInput, Scheduler};
+use crate::settings::{Settings, SettingsStore};
+use crate::slash::{self, SlashCommand};
+use crate::template;
+use crate::verify::{self, Expectation};
+
+/// Furthest step an action reached
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ActionStage {
+    Started,
+    /// Target window found and focus requested
+    Focused,
+    /// Cursor placed / target clicked
+    Positioned,
+    /// Keys or text sent
+    InputSent,
+    Done,
+}
+
+/// Result of an action command, returned to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ActionOutcome {
+    pub action: String,
+    pub success: bool,
+    pub stage: ActionStage,
+    pub elapsed_ms: u64,
+    pub error: Option<String>,
+    /// Whether the target window actually became the foreground window
+    pub focus_obtained: bool,
+    /// Screen point clicked, if any
+    pub x: Option<i32>,
+    pub y: Option<i32>,
+    /// Whether re-detection confirmed the expected transition (None = not verified)
+    pub verified: Option<bool>,
+    /// Times the action was performed (more than 1 when retried after verification)
+    pub attempts: u32,
+    /// Steps were only logged, not sent
+    pub dry_run: bool,
+    /// Steps a dry run would have sent
+    pub planned: Vec<InputAction>,
+}
+
+/// Progress of a running action sequence
+#[derive(Debug)]
+pub struct Progress {
+    pub stage: ActionStage,
+    pub focus_obtained: bool,
+    pub point: Option<(i32, i32)>,
+}
+
+impl Progress {
+    fn new() -> Self {
+        Progress {
+            stage: ActionStage::Started,
+            focus_obtained: false,
+            point: None,
+        }
+    }
+
+    fn focus(&mut self, input: &mut dyn InputBackend, window_handle: i64) -> Result<(), String> {
+        self.focus_obtained = input.focus(window_handle)?;
+        self.stage = ActionStage::Focused;
+        Ok(())
+    }
+
+    /// Focus and fail if the window didn't come to the foreground
+    /// (typing into another window is worse than not acting)
+    fn require_focus(
+        &mut self,
+        input: &mut dyn InputBackend,
+        window_handle: i64,
+    ) -> Result<(), String> {
+        self.focus(input, window_handle)?;
+        if !self.focus_obtained {
+            return Err("Window did not come to the foreground".to_string());
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, input: &mut dyn InputBackend, x: i32, y: i32) -> Result<(), String> {
+        input.click(x, y)?;
+        self.point = Some((x, y));
+        self.stage = ActionStage::Positioned;
+        Ok(())
+    }
+}
+
+/// Run an action sequence and time it
+pub fn run(action: &str, f: impl FnOnce(&mut Progress) -> Result<(), String>) -> ActionOutcome {
+    let start = std::time::Instant::now();
+    let mut progress = Progress::new();
+    let result = f(&mut progress);
+    if result.is_ok() {
+        progress.stage = ActionStage::Done;
+    }
+
+    let outcome = ActionOutcome {
+        action: action.to_string(),
+        success: result.is_ok(),
+        stage: progress.stage,
+        elapsed_ms: start.elapsed().as_millis() as u64,
+        error: result.err(),
+        focus_obtained: progress.focus_obtained,
+        x: progress.point.map(|p| p.0),
+        y: progress.point.map(|p| p.1),
+        verified: None,
+        attempts: 1,
+        dry_run: false,
+        planned: Vec::new(),
+    };
+    println!(
+        "[{}] success={} stage={:?} focus={} in {}ms{}",
+        action,
+        outcome.success,
+        outcome.stage,
+        outcome.focus_obtained,
+        outcome.elapsed_ms,
+        outcome
+            .error
+            .as_ref()
+            .map(|e| format!(" - {}", e))
+            .unwrap_or_default()
+    );
+    outcome
+}
+
+/// Managed state shared by the action commands
+pub struct ActionContext<'a> {
+    pub config: &'a ConfigStore,
+    pub settings: &'a SettingsStore,
+    pub runtime: &'a Runtime,
+    pub scheduler: &'a Scheduler,
+    /// Requested from BOB's own UI rather than by the supervisor
+    pub user_initiated: bool,
+}
+
+impl ActionContext<'_> {
+    /// Bound on both queue wait and execution for one action
+    pub fn timeout(&self) -> Duration {
+        Duration::from_secs(self.settings.get().action_timeout_seconds.max(1) as u64)
+    }
+
+    /// Whether an instance's input and notifications are only logged
+    pub fn dry_run(&self, project_name: Option<&str>) -> bool {
+        self.config
+            .get()
+            .dry_run_for(project_name, self.settings.get().dry_run)
+    }
+}
+
+/// Run with the managed state the action functions share
+/// (`user_initiated` = false: the supervisor acting on its own)
+pub fn with_context<R>(app: &AppHandle, f: impl FnOnce(&ActionContext) -> R) -> R {
+    context(app, false, f)
+}
+
+fn context<R>(app: &AppHandle, user_initiated: bool, f: impl FnOnce(&ActionContext) -> R) -> R {
+    let config = app.state::<ConfigStore>();
+    let settings = app.state::<SettingsStore>();
+    let runtime = app.state::<Runtime>();
+    let scheduler = app.state::<Scheduler>();
+    f(&ActionContext {
+        config: &config,
+        settings: &settings,
+        runtime: &runtime,
+        scheduler: &scheduler,
+        user_initiated,
+    })
+}
+
+/// Run a command's action on the blocking pool. Waiting for a queue turn and
+/// the input steps can take up to the action timeout, which would freeze the
+/// webview on the main thread.
+pub async fn blocking<R: Send + 'static>(
+    app: AppHandle,
+    f: impl FnOnce(&ActionContext) -> R + Send + 'static,
+) -> Result<R, String> {
+    tauri::async_runtime::spawn_blocking(move || context(&app, true, f))
+        .await
+        .map_err(|e| format!("Action task failed: {}", e))
+}
+
+/// `blocking` for commands that report an ActionOutcome
+async fn blocking_action(
+    app: AppHandle,
+    action: &str,
+    f: impl FnOnce(&ActionContext) -> ActionOutcome + Send + 'static,
+) -> ActionOutcome {
+    blocking(app, f)
+        .await
+        .unwrap_or_else(|e| run(action, |_| Err(e)))
+}
+
+/// Record an action's steps and log them as planned instead of sending them.
+/// Nothing takes focus, so dry runs skip the queue, idle guard and verification.
+pub fn run_dry(
+    settings: &Settings,
+    action: &str,
+    name: &str,
+    f: impl FnOnce(&mut dyn InputBackend, &mut Progress) -> Result<(), String>,
+) -> ActionOutcome {
+    let mut input = DryRunInput::new(input::native().ok());
+    let mut outcome = run(action, |progress| f(&mut input, progress));
+    outcome.dry_run = true;
+    outcome.planned = input.into_planned();
+    for step in &outcome.planned {
+        notify::log(
+            settings,
+            "DRYRUN",
+            &format!("[{}] {}: {}", name, action, step),
+        );
+    }
+    outcome
+}
+
+/// Run an action with the platform input backend, stopping between steps
+/// if its queue turn is cancelled or times out
+fn run_native(
+    action: &str,
+    token: &ActionToken,
+    f: impl FnOnce(&mut dyn InputBackend, &mut Progress) -> Result<(), String>,
+) -> ActionOutcome {
+    run(action, |progress| {
+        let mut backend = GuardedInput::new(input::native()?, token);
+        f(&mut backend, progress)
+    })
+}
+
+/// Run an action in the instance's queue turn, then re-detect to confirm it
+/// had the expected effect. Unconfirmed actions are repeated or escalated
+/// according to the instance's VerifyPolicy.
+fn run_verified(
+    ctx: &ActionContext,
+    action: &str,
+    window_handle: i64,
+    project_name: Option<&str>,
+    expectation: Expectation,
+    mut f: impl FnMut(&mut dyn InputBackend, &mut Progress) -> Result<(), String>,
+) -> ActionOutcome {
+    if ctx.dry_run(project_name) {
+        let name = project_name.unwrap_or("window");
+        return run_dry(&ctx.settings.get(), action, name, f);
+    }
+
+    let cfg = ctx.config.get();
+    let policy = cfg.verify_for(project_name);
+    let layout = cfg.layout_for(project_name);
+    let detection = cfg.detection_for(project_name);
+
+    let queued = ctx
+        .scheduler
+        .run(window_handle, action, ctx.timeout(), |token| {
+            // Postpone instead of taking focus while the user is active
+            idle::guard(ctx, window_handle)?;
+
+            let mut attempts = 0;
+            loop {
+                attempts += 1;
+                let mut outcome = run_native(action, token, &mut f);
+                outcome.attempts = attempts;
+                if !outcome.success || !policy.enabled {
+                    return Ok(outcome);
+                }
+
+                let confirmed =
+                    verify::confirm(window_handle, &layout, &detection, expectation, &policy);
+                outcome.verified = Some(confirmed);
+                if confirmed {
+                    return Ok(outcome);
+                }
+
+                if attempts <= policy.retries && expectation.can_retry(&policy) {
+                    println!(
+                        "[{}] {:?} not confirmed - retrying ({}/{})",
+                        action, expectation, attempts, policy.retries
+                    );
+                    continue;
+                }
+
+                let message = format!(
+                    "{} not confirmed after {} attempt(s): expected {:?}",
+                    action, attempts, expectation
+                );
+                if policy.escalate {
+                    escalate(
+                        ctx.settings,
+                        project_name.unwrap_or("window"),
+                        action,
+                        &message,
+                        attempts,
+                    );
+                }
+                outcome.success = false;
+                outcome.error = Some(message);
+                return Ok(outcome);
+            }
+        });
+
+    queued.unwrap_or_else(|e| run(action, |_| Err(e)))
+}
+
+/// Log and notify an action that never produced its expected UI change
+fn escalate(settings: &SettingsStore, name: &str, action: &str, message: &str, attempts: u32) {
+    let settings = settings.get();
+    notify::log(&settings, "WARN", &format!("[{}] {}", name, message));
+    if settings.notify_on_error {
+        notify::spawn_discord(
+            &settings,
+            false,
+            format!("⚠️ {} - Acción sin confirmar", name),
+            format!(
+                "La acción {} no produjo el cambio esperado en la UI ({} intentos).",
+                action, attempts
+            ),
+        );
+    }
+}
+
+/// Click a screen position inside an instance window
+pub fn perform_click(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_handle: i64,
+    x: i32,
+    y: i32,
+) -> Result<(), String> {
+    progress.focus(input, window_handle)?;
+    input.sleep(300);
+    progress.click(input, x, y)
+}
+
+/// Accept the current dialog with the profile's accept chord
+pub fn perform_accept_dialog(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_handle: i64,
+    keys: &KeybindingProfile,
+) -> Result<(), String> {
+    progress.focus(input, window_handle)?;
+    input.sleep(300);
+    input.key_chord(keys.require(EditorAction::Accept)?.keys())?;
+    progress.stage = ActionStage::InputSent;
+    input.sleep(150);
+    Ok(())
+}
+
+/// Scroll the chat to the bottom with the profile's scroll chord
+pub fn perform_scroll_to_bottom(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_handle: i64,
+    keys: &KeybindingProfile,
+) -> Result<(), String> {
+    progress.focus(input, window_handle)?;
+    input.sleep(200);
+    input.key_chord(keys.require(EditorAction::ScrollBottom)?.keys())?;
+    progress.stage = ActionStage::InputSent;
+    input.sleep(100);
+    Ok(())
+}
+
+/// Focus the chat input, enter the prompt and submit it.
+/// Prompts starting with a slash command go through the command popup.
+pub fn perform_write_to_chat(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_handle: i64,
+    layout: &LayoutProfile,
+    keys: &KeybindingProfile,
+    prompt: &str,
+) -> Result<(), String> {
+    progress.require_focus(input, window_handle)?;
+
+    let rect = input
+        .window_rect(window_handle)
+        .ok_or("Failed to get window rect")?;
+    // Chat input - calibrated, or the bottom right area
+    let (chat_x, chat_y) = match layout.chat_input {
+        Some(point) => point.to_screen(&rect),
+        None => (
+            rect.left + (rect.width() as f64 * 0.75) as i32,
+            rect.bottom - (rect.height() as f64 * 0.08).max(80.0) as i32,
+        ),
+    };
+    match (layout.chat_input, keys.chord(EditorAction::FocusChat)) {
+        // Editor shortcut that focuses the chat input
+        (None, Some(chord)) => {
+            input.key_chord(chord.keys())?;
+            progress.stage = ActionStage::Positioned;
+        }
+        _ => progress.click(input, chat_x, chat_y)?,
+    }
+
+    input.sleep(150);
+    match SlashCommand::parse(prompt) {
+        Some(slash) => slash::type_command(input, keys, &rect, (chat_x, chat_y), &slash)?,
+        None => input.paste(prompt)?,
+    }
+    progress.stage = ActionStage::InputSent;
+    input.sleep(200);
+    input.key_chord(keys.require(EditorAction::Submit)?.keys())
+}
+
+/// Open a fresh chat with the profile's new-chat chord and seed it with a prompt
+pub fn perform_new_chat(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_handle: i64,
+    layout: &LayoutProfile,
+    keys: &KeybindingProfile,
+    prompt: &str,
+) -> Result<(), String> {
+    let chord = keys.require(EditorAction::NewChat)?;
+    progress.require_focus(input, window_handle)?;
+    input.sleep(200);
+    input.key_chord(chord.keys())?;
+    // The panel re-renders before the new input is usable
+    input.sleep(1000);
+    perform_write_to_chat(input, progress, window_handle, layout, keys, prompt)
+}
+
+/// Focus the first window matching a title and paste + submit a prompt
+pub fn perform_paste_prompt(
+    input: &mut dyn InputBackend,
+    progress: &mut Progress,
+    window_title: &str,
+    keys: &KeybindingProfile,
+    prompt: &str,
+) -> Result<(), String> {
+    let window_handle = input
+        .find_window(window_title)
+        .or_else(|| input.find_window("Visual Studio Code"))
+        .ok_or_else(|| format!("No matching window found: {}", window_title))?;
+
+    progress.require_focus(input, window_handle)?;
+    input.sleep(300);
+    input.paste(prompt)?;
+    progress.stage = ActionStage::InputSent;
+    input.sleep(200);
+    input.key_chord(keys.require(EditorAction::Submit)?.keys())
+}
+
+/// Paste a prompt to a specific window
+#[tauri::command]
+pub async fn paste_prompt(
+    app: AppHandle,
+    window_title: String,
+    prompt: String,
+    instance_id: String,
+) -> ActionOutcome {
+    blocking_action(app, "paste_prompt", move |ctx| {
+        paste_to_window(ctx, &window_title, &prompt, &instance_id)
+    })
+    .await
+}
+
+fn paste_to_window(
+    ctx: &ActionContext,
+    window_title: &str,
+    prompt: &str,
+    instance_id: &str,
+) -> ActionOutcome {
+    let settings = ctx.settings;
+    println!("[{}] Looking for window: {}", instance_id, window_title);
+    // Only the window title is known here, so use the default keys
+    let keys = KeybindingProfile::antigravity();
+    if ctx.dry_run(Some(instance_id)) {
+        return run_dry(
+            &settings.get(),
+            "paste_prompt",
+            instance_id,
+            |input, progress| perform_paste_prompt(input, progress, window_title, &keys, prompt),
+        );
+    }
+
+    // The target window isn't known until the search runs; queue under handle 0
+    ctx.scheduler
+        .run(0, "paste_prompt", ctx.timeout(), |token| {
+            idle::check(
+                &SystemIdle,
+                settings.get().user_idle_seconds,
+                ctx.user_initiated,
+            )?;
+            Ok(run_native("paste_prompt", token, |input, progress| {
+                perform_paste_prompt(input, progress, window_title, &keys, prompt)
+            }))
+        })
+        .unwrap_or_else(|e| run("paste_prompt", |_| Err(e)))
+}
+
+/// Click a detected element and confirm it went away
+pub fn click(
+    ctx: &ActionContext,
+    window_handle: i64,
+    kind: ElementKind,
+    x: i32,
+    y: i32,
+    project_name: Option<&str>,
+) -> ActionOutcome {
+    run_verified(
+        ctx,
+        "click_button",
+        window_handle,
+        project_name,
+        Expectation::ElementGone { kind, x, y },
+        |input, progress| perform_click(input, progress, window_handle, x, y),
+    )
+}
+
+/// Accept the open dialog and confirm it closed
+pub fn accept(
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<&str>,
+) -> ActionOutcome {
+    let keys = ctx.config.get().keybindings_for(project_name);
+    run_verified(
+        ctx,
+        "accept_dialog",
+        window_handle,
+        project_name,
+        Expectation::DialogGone,
+        |input, progress| perform_accept_dialog(input, progress, window_handle, &keys),
+    )
+}
+
+/// Scroll the chat to the bottom and confirm the chat controls are visible
+pub fn scroll(
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<&str>,
+) -> ActionOutcome {
+    let keys = ctx.config.get().keybindings_for(project_name);
+    run_verified(
+        ctx,
+        "scroll_to_bottom",
+        window_handle,
+        project_name,
+        Expectation::ChatVisible,
+        |input, progress| perform_scroll_to_bottom(input, progress, window_handle, &keys),
+    )
+}
+
+/// Render a prompt template, send it and record it on the instance. A prompt
+/// that doesn't parse as a template (e.g. pasted code) is sent as written.
+pub fn write_prompt(
+    ctx: &ActionContext,
+    window_handle: i64,
+    prompt: &str,
+    project_name: Option<&str>,
+) -> ActionOutcome {
+    let rt = ctx.runtime.get(window_handle).unwrap_or_default();
+    let name = project_name.unwrap_or("window");
+    let prompt = match template::render(prompt, &template::vars(&rt, project_name)) {
+        Ok(rendered) => {
+            if !rendered.unknown.is_empty() {
+                notify::log(
+                    &ctx.settings.get(),
+                    "WARN",
+                    &format!(
+                        "[{}] Unknown prompt variables left as written: {}",
+                        name,
+                        rendered.unknown.join(", ")
+                    ),
+                );
+            }
+            rendered.text
+        }
+        Err(e) => {
+            notify::log(
+                &ctx.settings.get(),
+                "WARN",
+                &format!("[{}] Prompt sent without templating: {}", name, e),
+            );
+            prompt.to_string()
+        }
+    };
+    let prompt = prompt.as_str();
+
+    let cfg = ctx.config.get();
+    let layout = cfg.layout_for(project_name);
+    let keys = cfg.keybindings_for(project_name);
+    let outcome = run_verified(
+        ctx,
+        "write_to_chat",
+        window_handle,
+        project_name,
+        Expectation::Working,
+        |input, progress| {
+            perform_write_to_chat(input, progress, window_handle, &layout, &keys, prompt)
+        },
+    );
+
+    // An unconfirmed send may still have gone through; record the prompt
+    // whenever it reached the chat so the next response read can find it
+    if outcome.stage >= ActionStage::InputSent && !outcome.dry_run {
+        ctx.runtime.with(window_handle, |rt| {
+            rt.last_prompt = Some(prompt.to_string());
+            rt.last_prompt_at = crate::runtime::now_ms();
+            rt.prompts_since_new_chat += 1;
+        });
+    }
+    outcome
+}
+
+/// Open a new chat seeded with the carry-over summary and reset the chat state
+pub fn open_new_chat(
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<&str>,
+    reason: Option<&str>,
+    blockers: &[String],
+) -> ActionOutcome {
+    let cfg = ctx.config.get();
+    let layout = cfg.layout_for(project_name);
+    let keys = cfg.keybindings_for(project_name);
+    let name = project_name.unwrap_or("window");
+    let rt = ctx.runtime.get(window_handle).unwrap_or_default();
+    let prompt = carryover::build_prompt(name, &rt, blockers);
+
+    notify::log(
+        &ctx.settings.get(),
+        "INFO",
+        &format!(
+            "[{}] Opening new chat ({})",
+            name,
+            reason.unwrap_or("manual")
+        ),
+    );
+    let outcome = run_verified(
+        ctx,
+        "new_chat",
+        window_handle,
+        project_name,
+        Expectation::Working,
+        |input, progress| perform_new_chat(input, progress, window_handle, &layout, &keys, &prompt),
+    );
+
+    // The carry-over prompt starts the new chat's count
+    if outcome.stage >= ActionStage::InputSent && !outcome.dry_run {
+        ctx.runtime.with(window_handle, |rt| {
+            rt.last_prompt = Some(prompt);
+            rt.last_prompt_at = crate::runtime::now_ms();
+            rt.last_response = None;
+            rt.prompts_since_new_chat = 1;
+            rt.blockers.clear();
+        });
+    }
+    outcome
+}
+
+/// Click a button at screen coordinates (an Accept button unless `kind` says otherwise)
+#[tauri::command]
+pub async fn click_button(
+    app: AppHandle,
+    window_handle: i64,
+    screen_x: i32,
+    screen_y: i32,
+    kind: Option<ElementKind>,
+    project_name: Option<String>,
+) -> ActionOutcome {
+    blocking_action(app, "click_button", move |ctx| {
+        click(
+            ctx,
+            window_handle,
+            kind.unwrap_or(ElementKind::Accept),
+            screen_x,
+            screen_y,
+            project_name.as_deref(),
+        )
+    })
+    .await
+}
+
+/// Accept dialog using the instance's accept chord
+#[tauri::command]
+pub async fn accept_dialog(
+    app: AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> ActionOutcome {
+    blocking_action(app, "accept_dialog", move |ctx| {
+        accept(ctx, window_handle, project_name.as_deref())
+    })
+    .await
+}
+
+/// Scroll chat to bottom using the instance's scroll chord
+#[tauri::command]
+pub async fn scroll_to_bottom(
+    app: AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> ActionOutcome {
+    blocking_action(app, "scroll_to_bottom", move |ctx| {
+        scroll(ctx, window_handle, project_name.as_deref())
+    })
+    .await
+}
+
+/// Write to chat and submit prompt
+#[tauri::command]
+pub async fn write_to_chat(
+    app: AppHandle,
+    window_handle: i64,
+    prompt: String,
+    project_name: Option<String>,
+) -> ActionOutcome {
+    blocking_action(app, "write_to_chat", move |ctx| {
+        write_prompt(ctx, window_handle, &prompt, project_name.as_deref())
+    })
+    .await
+}
+
+/// Set or clear an instance's dry-run override (None = follow the global setting)
+#[tauri::command]
+pub fn set_instance_dry_run(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: String,
+    dry_run: Option<bool>,
+) -> Result<(), String> {
+    config.update(|c| {
+        c.instances.entry(project_name).or_default().dry_run = dry_run;
+    })
+}
+
+/// Open a new chat in the instance, seeded with a carry-over summary
+#[tauri::command]
+pub async fn new_chat(
+    app: AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+    reason: Option<String>,
+    blockers: Option<Vec<String>>,
+) -> ActionOutcome {
+    blocking_action(app, "new_chat", move |ctx| {
+        open_new_chat(
+            ctx,
+            window_handle,
+            project_name.as_deref(),
+            reason.as_deref(),
+            &blockers.unwrap_or_default(),
+        )
+    })
+    .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::{Key, RecordingInput};
+    use crate::keymap::Chord;
+    use crate::layout::{Point, WindowRect};
+
+    const HANDLE: i64 = 42;
+
+    fn recorder() -> RecordingInput {
+        RecordingInput::new(WindowRect {
+            left: 100,
+            top: 50,
+            right: 1100,
+            bottom: 850,
+        })
+    }
+
+    fn focus() -> InputAction {
+        InputAction::Focus {
+            window_handle: HANDLE,
+        }
+    }
+
+    fn chord(keys: &[Key]) -> InputAction {
+        InputAction::Chord {
+            keys: keys.to_vec(),
+        }
+    }
+
+    fn paste(text: &str) -> InputAction {
+        InputAction::Paste {
+            text: text.to_string(),
+        }
+    }
+
+    #[test]
+    fn click_focuses_then_clicks() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        perform_click(&mut input, &mut progress, HANDLE, 300, 400).unwrap();
+
+        assert_eq!(
+            input.actions,
+            vec![focus(), InputAction::Click { x: 300, y: 400 }]
+        );
+        assert_eq!(progress.stage, ActionStage::Positioned);
+        assert_eq!(progress.point, Some((300, 400)));
+    }
+
+    #[test]
+    fn accept_dialog_sends_accept_chord() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        let keys = KeybindingProfile::antigravity();
+        perform_accept_dialog(&mut input, &mut progress, HANDLE, &keys).unwrap();
+
+        assert_eq!(input.actions, vec![focus(), chord(&[Key::ALT, Key::ENTER])]);
+        assert_eq!(progress.stage, ActionStage::InputSent);
+    }
+
+    #[test]
+    fn write_to_chat_clicks_default_input_position() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        let layout = LayoutProfile::right();
+        let keys = KeybindingProfile::antigravity();
+        perform_write_to_chat(&mut input, &mut progress, HANDLE, &layout, &keys, "hola").unwrap();
+
+        // 75% across, 80px above the bottom of a 1000x800 window
+        assert_eq!(
+            input.actions,
+            vec![
+                focus(),
+                InputAction::Click { x: 850, y: 770 },
+                paste("hola"),
+                chord(&[Key::ENTER]),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_to_chat_clicks_calibrated_input() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        let mut layout = LayoutProfile::right();
+        layout.chat_input = Some(Point { x: 0.5, y: 0.95 });
+        let keys = KeybindingProfile::antigravity();
+        perform_write_to_chat(&mut input, &mut progress, HANDLE, &layout, &keys, "hola").unwrap();
+
+        assert_eq!(
+            input.actions,
+            vec![
+                focus(),
+                InputAction::Click { x: 600, y: 810 },
+                paste("hola"),
+                chord(&[Key::ENTER]),
+            ]
+        );
+    }
+
+    #[test]
+    fn write_to_chat_stops_without_focus() {
+        let mut input = recorder();
+        input.focus_ok = false;
+        let mut progress = Progress::new();
+        let layout = LayoutProfile::right();
+        let keys = KeybindingProfile::antigravity();
+        let result =
+            perform_write_to_chat(&mut input, &mut progress, HANDLE, &layout, &keys, "hola");
+
+        assert!(result.is_err());
+        assert_eq!(input.actions, vec![focus()]);
+        assert_eq!(progress.stage, ActionStage::Focused);
+    }
+
+    #[test]
+    fn new_chat_sends_chord_then_prompt() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        let layout = LayoutProfile::right();
+        let mut keys = KeybindingProfile::antigravity();
+        keys.new_chat = Some(Chord(vec![Key::CONTROL, Key::char('l')]));
+        perform_new_chat(&mut input, &mut progress, HANDLE, &layout, &keys, "resumen").unwrap();
+
+        assert_eq!(
+            input.actions,
+            vec![
+                focus(),
+                chord(&[Key::CONTROL, Key::char('l')]),
+                focus(),
+                InputAction::Click { x: 850, y: 770 },
+                paste("resumen"),
+                chord(&[Key::ENTER]),
+            ]
+        );
+    }
+
+    #[test]
+    fn new_chat_requires_binding() {
+        let mut input = recorder();
+        let mut progress = Progress::new();
+        let layout = LayoutProfile::right();
+        let mut keys = KeybindingProfile::antigravity();
+        keys.new_chat = None;
+        let result = perform_new_chat(&mut input, &mut progress, HANDLE, &layout, &keys, "resumen");
+
+        assert!(result.is_err());
+        assert!(input.actions.is_empty());
+    }
+}
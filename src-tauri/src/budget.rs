@@ -0,0 +1,439 @@
+// Per-instance budgets
+// Caps on prompts per hour and per day, and on prompts and wall-clock time
+// spent on one issue (tracked by the backlog's current issue). Each cap has
+// its own over-budget action: pause the instance, skip the issue (the agent is
+// told to mark it blocked and move on), or just notify once.
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::runtime::InstanceRuntime;
+
+const HOUR_MS: u64 = 60 * 60 * 1000;
+const DAY_MS: u64 = 24 * HOUR_MS;
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum OverBudgetAction {
+    /// Take the instance out of the loop until it's re-enabled
+    #[default]
+    Pause,
+    /// Have the agent mark the issue blocked and continue with the next one
+    SkipIssue,
+    /// Notify once and keep going
+    Notify,
+}
+
+/// One cap (0 = unlimited) and what to do when it's reached
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BudgetLimit {
+    pub limit: u32,
+    pub action: OverBudgetAction,
+}
+
+/// Budgets, global or per instance
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BudgetPolicy {
+    pub prompts_per_hour: BudgetLimit,
+    pub prompts_per_day: BudgetLimit,
+    /// Prompts sent while one issue is current
+    pub steps_per_issue: BudgetLimit,
+    /// Minutes since the issue became current
+    pub minutes_per_issue: BudgetLimit,
+    /// Prompt template sent to skip an issue
+    pub skip_prompt: String,
+}
+
+impl Default for BudgetPolicy {
+    fn default() -> Self {
+        BudgetPolicy {
+            prompts_per_hour: BudgetLimit::default(),
+            prompts_per_day: BudgetLimit::default(),
+            steps_per_issue: BudgetLimit::default(),
+            minutes_per_issue: BudgetLimit::default(),
+            skip_prompt: "El issue {{currentIssue}} superó su presupuesto. Márcalo como bloqueado en el backlog con el motivo y continúa con el siguiente issue.".to_string(),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum BudgetKind {
+    PromptsPerHour,
+    PromptsPerDay,
+    StepsPerIssue,
+    MinutesPerIssue,
+}
+
+impl BudgetKind {
+    fn per_issue(self) -> bool {
+        matches!(
+            self,
+            BudgetKind::StepsPerIssue | BudgetKind::MinutesPerIssue
+        )
+    }
+}
+
+/// What the instance has used
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct BudgetUsage {
+    /// Prompt times in the last day, oldest first
+    #[serde(skip)]
+    pub prompts: VecDeque<u64>,
+    pub prompts_last_hour: u32,
+    pub prompts_last_day: u32,
+    /// Issue being counted, since when, and prompts sent on it
+    pub issue: String,
+    pub issue_started_at: u64,
+    pub issue_steps: u32,
+    /// Issues skipped for going over budget
+    pub skipped: Vec<String>,
+    /// Budgets already notified (per issue ones reset with the issue)
+    #[serde(skip)]
+    pub notified: Vec<BudgetKind>,
+}
+
+impl BudgetUsage {
+    fn count(&mut self, now: u64) {
+        while self.prompts.front().is_some_and(|&t| t + DAY_MS <= now) {
+            self.prompts.pop_front();
+        }
+        self.prompts_last_day = self.prompts.len() as u32;
+        self.prompts_last_hour = self.prompts.iter().filter(|&&t| t + HOUR_MS > now).count() as u32;
+    }
+
+    /// Start counting a new current issue
+    fn track_issue(&mut self, issue: &str, now: u64) {
+        if self.issue != issue || self.issue_started_at == 0 {
+            self.issue = issue.to_string();
+            self.issue_started_at = now;
+            self.issue_steps = 0;
+            self.notified.retain(|k| !k.per_issue());
+        }
+    }
+}
+
+/// A cap the instance reached
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exceeded {
+    pub kind: BudgetKind,
+    pub action: OverBudgetAction,
+    /// e.g. "30/30 prompts in the last hour"
+    pub detail: String,
+    /// The issue was already skipped once and is still current
+    pub already_skipped: bool,
+}
+
+fn current_issue(rt: &InstanceRuntime) -> String {
+    rt.backlog
+        .as_ref()
+        .map(|b| b.current_issue.clone())
+        .unwrap_or_default()
+}
+
+/// Check the budgets before a prompt goes out. Returns the first cap
+/// reached; notify-only caps are returned once until they reset.
+pub fn check(policy: &BudgetPolicy, rt: &mut InstanceRuntime, now: u64) -> Option<Exceeded> {
+    let issue = current_issue(rt);
+    let usage = &mut rt.budget;
+    usage.count(now);
+    usage.track_issue(&issue, now);
+
+    let issue_minutes = (now.saturating_sub(usage.issue_started_at) / 60000) as u32;
+    let checks = [
+        (
+            BudgetKind::PromptsPerHour,
+            &policy.prompts_per_hour,
+            usage.prompts_last_hour,
+            "prompts in the last hour",
+        ),
+        (
+            BudgetKind::PromptsPerDay,
+            &policy.prompts_per_day,
+            usage.prompts_last_day,
+            "prompts in the last day",
+        ),
+        (
+            BudgetKind::StepsPerIssue,
+            &policy.steps_per_issue,
+            usage.issue_steps,
+            "prompts on the current issue",
+        ),
+        (
+            BudgetKind::MinutesPerIssue,
+            &policy.minutes_per_issue,
+            issue_minutes,
+            "minutes on the current issue",
+        ),
+    ];
+
+    for (kind, budget, used, what) in checks {
+        if budget.limit == 0 {
+            continue;
+        }
+        if used < budget.limit {
+            // Rolling windows free up again; notify the next time they fill
+            if !kind.per_issue() {
+                usage.notified.retain(|&k| k != kind);
+            }
+            continue;
+        }
+        if budget.action == OverBudgetAction::Notify {
+            if usage.notified.contains(&kind) {
+                continue;
+            }
+            usage.notified.push(kind);
+        }
+        return Some(Exceeded {
+            kind,
+            action: budget.action,
+            detail: format!("{}/{} {}", used, budget.limit, what),
+            already_skipped: !issue.is_empty() && usage.skipped.contains(&issue),
+        });
+    }
+    None
+}
+
+/// Count a prompt sent to the instance
+pub fn record_prompt(rt: &mut InstanceRuntime, now: u64) {
+    rt.budget.prompts.push_back(now);
+    rt.budget.issue_steps += 1;
+    rt.budget.count(now);
+}
+
+/// Give the current issue a fresh step and time budget (re-enabled by hand)
+pub fn reset_issue(rt: &mut InstanceRuntime, now: u64) {
+    rt.budget.issue_started_at = now;
+    rt.budget.issue_steps = 0;
+    rt.budget.notified.clear();
+}
+
+/// Remember that the current issue was skipped
+pub fn record_skip(rt: &mut InstanceRuntime) {
+    let issue = current_issue(rt);
+    if !issue.is_empty() && !rt.budget.skipped.contains(&issue) {
+        rt.budget.skipped.push(issue);
+    }
+}
+
+/// Get the budget policy for an instance (or the global one)
+#[tauri::command]
+pub fn get_budget_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> BudgetPolicy {
+    config.get().budget_for(project_name.as_deref())
+}
+
+/// Set the global budget policy, or an instance override when project_name is given
+#[tauri::command]
+pub fn set_budget_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    policy: BudgetPolicy,
+) -> Result<(), String> {
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .budget = Some(policy);
+        }
+        None => c.budget = policy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BacklogResult;
+
+    const MINUTE_MS: u64 = 60 * 1000;
+    /// Fixed start time, well past 0 so windows can look back a day
+    const T0: u64 = 10 * DAY_MS;
+
+    fn on_issue(issue: &str) -> InstanceRuntime {
+        let mut rt = InstanceRuntime::default();
+        set_issue(&mut rt, issue);
+        rt
+    }
+
+    fn set_issue(rt: &mut InstanceRuntime, issue: &str) {
+        rt.backlog = Some(BacklogResult {
+            current_issue: issue.to_string(),
+            ..BacklogResult::default()
+        });
+    }
+
+    fn limit(limit: u32, action: OverBudgetAction) -> BudgetLimit {
+        BudgetLimit { limit, action }
+    }
+
+    #[test]
+    fn unlimited_by_default() {
+        let mut rt = on_issue("#1");
+        for i in 0..100 {
+            record_prompt(&mut rt, T0 + i);
+        }
+        assert_eq!(check(&BudgetPolicy::default(), &mut rt, T0 + 100), None);
+    }
+
+    #[test]
+    fn hourly_window_rolls_over() {
+        let policy = BudgetPolicy {
+            prompts_per_hour: limit(3, OverBudgetAction::Pause),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        for i in 0..3 {
+            record_prompt(&mut rt, T0 + i * MINUTE_MS);
+        }
+        assert_eq!(rt.budget.prompts_last_hour, 3);
+
+        let over = check(&policy, &mut rt, T0 + 10 * MINUTE_MS).unwrap();
+        assert_eq!(over.kind, BudgetKind::PromptsPerHour);
+        assert_eq!(over.action, OverBudgetAction::Pause);
+        assert_eq!(over.detail, "3/3 prompts in the last hour");
+
+        // The first prompt leaves the window an hour after it was sent
+        assert_eq!(check(&policy, &mut rt, T0 + HOUR_MS), None);
+        assert_eq!(rt.budget.prompts_last_hour, 2);
+        assert_eq!(rt.budget.prompts_last_day, 3);
+    }
+
+    #[test]
+    fn daily_window_rolls_over() {
+        let policy = BudgetPolicy {
+            prompts_per_day: limit(2, OverBudgetAction::Pause),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        record_prompt(&mut rt, T0);
+        record_prompt(&mut rt, T0 + 5 * HOUR_MS);
+        assert_eq!(
+            check(&policy, &mut rt, T0 + 6 * HOUR_MS).map(|o| o.kind),
+            Some(BudgetKind::PromptsPerDay)
+        );
+        assert_eq!(check(&policy, &mut rt, T0 + DAY_MS), None);
+        assert_eq!(rt.budget.prompts.len(), 1);
+    }
+
+    #[test]
+    fn notify_once_per_breach() {
+        let policy = BudgetPolicy {
+            prompts_per_hour: limit(1, OverBudgetAction::Notify),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        record_prompt(&mut rt, T0);
+
+        let over = check(&policy, &mut rt, T0 + MINUTE_MS).unwrap();
+        assert_eq!(over.action, OverBudgetAction::Notify);
+        assert_eq!(check(&policy, &mut rt, T0 + 2 * MINUTE_MS), None);
+        record_prompt(&mut rt, T0 + 3 * MINUTE_MS);
+        assert_eq!(check(&policy, &mut rt, T0 + 4 * MINUTE_MS), None);
+
+        // Once the window frees up, the next breach notifies again
+        assert_eq!(check(&policy, &mut rt, T0 + 2 * HOUR_MS), None);
+        record_prompt(&mut rt, T0 + 2 * HOUR_MS);
+        assert!(check(&policy, &mut rt, T0 + 2 * HOUR_MS + MINUTE_MS).is_some());
+    }
+
+    #[test]
+    fn pause_and_skip_are_returned_every_time() {
+        for action in [OverBudgetAction::Pause, OverBudgetAction::SkipIssue] {
+            let policy = BudgetPolicy {
+                steps_per_issue: limit(2, action),
+                ..BudgetPolicy::default()
+            };
+            let mut rt = on_issue("#1");
+            check(&policy, &mut rt, T0);
+            record_prompt(&mut rt, T0);
+            record_prompt(&mut rt, T0 + MINUTE_MS);
+            for now in [T0 + 2 * MINUTE_MS, T0 + 3 * MINUTE_MS] {
+                let over = check(&policy, &mut rt, now).unwrap();
+                assert_eq!(over.kind, BudgetKind::StepsPerIssue);
+                assert_eq!(over.action, action);
+                assert_eq!(over.detail, "2/2 prompts on the current issue");
+            }
+        }
+    }
+
+    #[test]
+    fn skipped_issue_is_reported() {
+        let policy = BudgetPolicy {
+            minutes_per_issue: limit(30, OverBudgetAction::SkipIssue),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        assert_eq!(check(&policy, &mut rt, T0), None);
+
+        let over = check(&policy, &mut rt, T0 + 30 * MINUTE_MS).unwrap();
+        assert_eq!(over.detail, "30/30 minutes on the current issue");
+        assert!(!over.already_skipped);
+
+        record_skip(&mut rt);
+        record_skip(&mut rt);
+        assert_eq!(rt.budget.skipped, vec!["#1"]);
+        assert!(
+            check(&policy, &mut rt, T0 + 31 * MINUTE_MS)
+                .unwrap()
+                .already_skipped
+        );
+
+        // The agent moved on: the new issue starts with a fresh budget
+        set_issue(&mut rt, "#2");
+        assert_eq!(check(&policy, &mut rt, T0 + 32 * MINUTE_MS), None);
+        assert_eq!(rt.budget.issue, "#2");
+        assert_eq!(rt.budget.issue_started_at, T0 + 32 * MINUTE_MS);
+    }
+
+    #[test]
+    fn new_issue_resets_per_issue_counts() {
+        let policy = BudgetPolicy {
+            steps_per_issue: limit(1, OverBudgetAction::Notify),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        check(&policy, &mut rt, T0);
+        record_prompt(&mut rt, T0);
+        assert!(check(&policy, &mut rt, T0 + 1).is_some());
+        assert_eq!(check(&policy, &mut rt, T0 + 2), None);
+
+        set_issue(&mut rt, "#2");
+        assert_eq!(check(&policy, &mut rt, T0 + 3), None);
+        assert_eq!(rt.budget.issue_steps, 0);
+        record_prompt(&mut rt, T0 + 4);
+        assert!(check(&policy, &mut rt, T0 + 5).is_some());
+    }
+
+    #[test]
+    fn reset_issue_restores_the_budget() {
+        let policy = BudgetPolicy {
+            steps_per_issue: limit(1, OverBudgetAction::Pause),
+            ..BudgetPolicy::default()
+        };
+        let mut rt = on_issue("#1");
+        check(&policy, &mut rt, T0);
+        record_prompt(&mut rt, T0);
+        assert!(check(&policy, &mut rt, T0 + MINUTE_MS).is_some());
+
+        reset_issue(&mut rt, T0 + 2 * MINUTE_MS);
+        assert_eq!(rt.budget.issue_steps, 0);
+        assert_eq!(rt.budget.issue_started_at, T0 + 2 * MINUTE_MS);
+        assert_eq!(check(&policy, &mut rt, T0 + 3 * MINUTE_MS), None);
+        // Rolling counts are not part of the issue budget
+        assert_eq!(rt.budget.prompts_last_hour, 1);
+    }
+
+    #[test]
+    fn record_skip_needs_an_issue() {
+        let mut rt = on_issue("");
+        record_skip(&mut rt);
+        assert!(rt.budget.skipped.is_empty());
+    }
+}
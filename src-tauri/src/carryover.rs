@@ -0,0 +1,73 @@
+// Context reset: when to open a fresh chat and what to seed it with
+// Long sessions overflow the agent's context; the carry-over prompt gives the
+// new chat enough state (backlog position, recent work, blockers) to continue
+
+use crate::runtime::{InstanceRuntime, Runtime};
+use crate::settings::{Settings, SettingsStore};
+
+/// Why the instance should start a new chat now, if it should
+pub fn due(settings: &Settings, rt: &InstanceRuntime) -> Option<String> {
+    if settings.new_chat_after_steps > 0
+        && rt.prompts_since_new_chat >= settings.new_chat_after_steps
+    {
+        return Some(format!(
+            "{} prompts in this chat",
+            rt.prompts_since_new_chat
+        ));
+    }
+    if !settings.new_chat_on_degradation {
+        return None;
+    }
+    let response = rt.last_response.as_deref()?.to_lowercase();
+    settings
+        .degradation_phrases
+        .iter()
+        .find(|p| !p.is_empty() && response.contains(&p.to_lowercase()))
+        .map(|p| format!("response mentions \"{}\"", p))
+}
+
+/// Opening prompt for the new chat
+pub fn build_prompt(project_name: &str, rt: &InstanceRuntime, blockers: &[String]) -> String {
+    let mut lines = vec![format!(
+        "Nuevo chat para continuar el proyecto {} (el contexto anterior se reinició).",
+        project_name
+    )];
+
+    if let Some(ref backlog) = rt.backlog {
+        lines.push(format!(
+            "Progreso del backlog: {}/{} issues completados.",
+            backlog.completed_issues, backlog.total_issues
+        ));
+        if !backlog.current_issue.is_empty() && backlog.current_issue != "DONE" {
+            lines.push(format!("Issue actual: {}", backlog.current_issue));
+        }
+        if !backlog.recent_completed.is_empty() {
+            lines.push("Últimos issues completados:".to_string());
+            lines.extend(backlog.recent_completed.iter().map(|i| format!("- {}", i)));
+        }
+    }
+
+    let mut open: Vec<&String> = rt.blockers.iter().collect();
+    open.extend(blockers.iter().filter(|b| !rt.blockers.contains(b)));
+    if !open.is_empty() {
+        lines.push("Bloqueos abiertos:".to_string());
+        lines.extend(open.iter().map(|b| format!("- {}", b)));
+    }
+
+    lines.push(
+        "Revisa el estado del repositorio y del backlog y continúa con el issue actual."
+            .to_string(),
+    );
+    lines.join("\n")
+}
+
+/// Whether a new chat is due for the instance, and why
+#[tauri::command]
+pub fn new_chat_due(
+    settings: tauri::State<'_, SettingsStore>,
+    runtime: tauri::State<'_, Runtime>,
+    window_handle: i64,
+) -> Option<String> {
+    let rt = runtime.get(window_handle)?;
+    due(&settings.get(), &rt)
+}
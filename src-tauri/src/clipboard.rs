@@ -0,0 +1,267 @@
+// Windows clipboard access
+// Text get/set plus whole-clipboard snapshots, so pasting a prompt can put
+// back whatever the user had copied
+
+/// Attempts to open a clipboard held by another application
+#[cfg(target_os = "windows")]
+const OPEN_ATTEMPTS: u32 = 10;
+
+/// Clipboard contents captured before we overwrite it
+#[derive(Debug, Default)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub struct ClipboardSnapshot {
+    /// (format, raw bytes) for every memory-backed format
+    formats: Vec<(u32, Vec<u8>)>,
+    /// Formats present but not restorable (bitmaps, metafiles, owner-drawn)
+    pub skipped: Vec<u32>,
+}
+
+impl ClipboardSnapshot {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn is_empty(&self) -> bool {
+        self.formats.is_empty()
+    }
+}
+
+/// Open clipboard, closed on drop
+#[cfg(target_os = "windows")]
+struct OpenClipboard;
+
+#[cfg(target_os = "windows")]
+impl OpenClipboard {
+    /// Open the clipboard, retrying briefly if another app holds it
+    fn open() -> Result<Self, String> {
+        use winapi::um::winuser::{GetOpenClipboardWindow, GetWindowTextW};
+
+        for _ in 0..OPEN_ATTEMPTS {
+            if unsafe { winapi::um::winuser::OpenClipboard(std::ptr::null_mut()) } != 0 {
+                return Ok(OpenClipboard);
+            }
+            std::thread::sleep(std::time::Duration::from_millis(20));
+        }
+
+        // Name the window holding it so the error is actionable
+        let owner = unsafe {
+            let hwnd = GetOpenClipboardWindow();
+            let mut buf = [0u16; 256];
+            let len = if hwnd.is_null() {
+                0
+            } else {
+                GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32)
+            };
+            String::from_utf16_lossy(&buf[..len.max(0) as usize])
+        };
+        Err(if owner.is_empty() {
+            "Clipboard is locked by another application".to_string()
+        } else {
+            format!("Clipboard is locked by \"{}\"", owner)
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for OpenClipboard {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::winuser::CloseClipboard();
+        }
+    }
+}
+
+/// Copy a global memory block into the clipboard for a format
+#[cfg(target_os = "windows")]
+unsafe fn set_data(format: u32, bytes: &[u8]) -> Result<(), String> {
+    use winapi::um::winbase::{GlobalAlloc, GlobalFree, GlobalLock, GlobalUnlock, GMEM_MOVEABLE};
+    use winapi::um::winuser::SetClipboardData;
+
+    let mem = GlobalAlloc(GMEM_MOVEABLE, bytes.len().max(1));
+    if mem.is_null() {
+        return Err("Failed to allocate clipboard memory".to_string());
+    }
+    let ptr = GlobalLock(mem) as *mut u8;
+    std::ptr::copy_nonoverlapping(bytes.as_ptr(), ptr, bytes.len());
+    GlobalUnlock(mem);
+    if SetClipboardData(format, mem).is_null() {
+        GlobalFree(mem);
+        return Err(format!("Failed to set clipboard data (format {})", format));
+    }
+    Ok(())
+}
+
+/// Read the clipboard as text (None if empty or not text)
+#[cfg(target_os = "windows")]
+pub fn get_text() -> Result<Option<String>, String> {
+    use winapi::um::winbase::{GlobalLock, GlobalUnlock};
+    use winapi::um::winuser::{GetClipboardData, CF_UNICODETEXT};
+
+    let _clipboard = OpenClipboard::open()?;
+    unsafe {
+        let handle = GetClipboardData(CF_UNICODETEXT);
+        if handle.is_null() {
+            return Ok(None);
+        }
+        let ptr = GlobalLock(handle) as *const u16;
+        if ptr.is_null() {
+            return Ok(None);
+        }
+        let mut len = 0;
+        while *ptr.add(len) != 0 {
+            len += 1;
+        }
+        let text = String::from_utf16_lossy(std::slice::from_raw_parts(ptr, len));
+        GlobalUnlock(handle);
+        Ok(Some(text))
+    }
+}
+
+/// Replace the clipboard contents with text
+#[cfg(target_os = "windows")]
+pub fn set_text(text: &str) -> Result<(), String> {
+    use winapi::um::winuser::{EmptyClipboard, CF_UNICODETEXT};
+
+    let bytes: Vec<u8> = text
+        .encode_utf16()
+        .chain(std::iter::once(0))
+        .flat_map(|u| u.to_le_bytes())
+        .collect();
+
+    let _clipboard = OpenClipboard::open()?;
+    unsafe {
+        EmptyClipboard();
+        set_data(CF_UNICODETEXT, &bytes)
+    }
+}
+
+/// Capture every memory-backed clipboard format
+#[cfg(target_os = "windows")]
+pub fn snapshot() -> Result<ClipboardSnapshot, String> {
+    use winapi::um::winbase::{GlobalLock, GlobalSize, GlobalUnlock};
+    use winapi::um::winuser::{
+        EnumClipboardFormats, GetClipboardData, CF_BITMAP, CF_DSPBITMAP, CF_DSPENHMETAFILE,
+        CF_DSPMETAFILEPICT, CF_ENHMETAFILE, CF_METAFILEPICT, CF_OWNERDISPLAY, CF_PALETTE,
+    };
+
+    // GDI-handle formats; Windows synthesizes bitmaps from CF_DIB anyway
+    let handle_formats = [
+        CF_BITMAP,
+        CF_METAFILEPICT,
+        CF_PALETTE,
+        CF_ENHMETAFILE,
+        CF_OWNERDISPLAY,
+        CF_DSPBITMAP,
+        CF_DSPMETAFILEPICT,
+        CF_DSPENHMETAFILE,
+    ];
+
+    let _clipboard = OpenClipboard::open()?;
+    let mut snapshot = ClipboardSnapshot::default();
+    unsafe {
+        let mut format = EnumClipboardFormats(0);
+        while format != 0 {
+            if handle_formats.contains(&format) {
+                snapshot.skipped.push(format);
+            } else {
+                let handle = GetClipboardData(format);
+                let ptr = if handle.is_null() {
+                    std::ptr::null_mut()
+                } else {
+                    GlobalLock(handle)
+                };
+                if ptr.is_null() {
+                    snapshot.skipped.push(format);
+                } else {
+                    let size = GlobalSize(handle);
+                    let bytes = std::slice::from_raw_parts(ptr as *const u8, size).to_vec();
+                    GlobalUnlock(handle);
+                    snapshot.formats.push((format, bytes));
+                }
+            }
+            format = EnumClipboardFormats(format);
+        }
+    }
+    Ok(snapshot)
+}
+
+/// Put a snapshot back (an empty snapshot empties the clipboard). A format
+/// that fails doesn't stop the rest; the failures are reported together.
+#[cfg(target_os = "windows")]
+pub fn restore(snapshot: &ClipboardSnapshot) -> Result<(), String> {
+    use winapi::um::winuser::EmptyClipboard;
+
+    let _clipboard = OpenClipboard::open()?;
+    let mut errors = Vec::new();
+    unsafe {
+        EmptyClipboard();
+        for (format, bytes) in &snapshot.formats {
+            if let Err(e) = set_data(*format, bytes) {
+                errors.push(e);
+            }
+        }
+    }
+    if errors.is_empty() {
+        Ok(())
+    } else {
+        Err(errors.join("; "))
+    }
+}
+
+/// Snapshot of the user's clipboard, put back on drop so every exit path
+/// (including errors partway through a paste or copy) restores it
+#[cfg(target_os = "windows")]
+pub struct ClipboardGuard(ClipboardSnapshot);
+
+#[cfg(target_os = "windows")]
+impl ClipboardGuard {
+    pub fn save() -> Result<Self, String> {
+        snapshot().map(ClipboardGuard)
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ClipboardGuard {
+    fn drop(&mut self) {
+        if let Err(e) = restore(&self.0) {
+            println!("[clipboard] Failed to restore clipboard: {}", e);
+        } else if !self.0.skipped.is_empty() {
+            println!(
+                "[clipboard] Clipboard restored without formats {:?}",
+                self.0.skipped
+            );
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn get_text() -> Result<Option<String>, String> {
+    Err("Clipboard only supported on Windows".to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn set_text(_text: &str) -> Result<(), String> {
+    Err("Clipboard only supported on Windows".to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn snapshot() -> Result<ClipboardSnapshot, String> {
+    Err("Clipboard only supported on Windows".to_string())
+}
+
+#[cfg(not(target_os = "windows"))]
+#[allow(dead_code)]
+pub fn restore(_snapshot: &ClipboardSnapshot) -> Result<(), String> {
+    Err("Clipboard only supported on Windows".to_string())
+}
+
+/// Put text on the clipboard and check it actually landed there,
+/// so a paste never sends stale contents
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn set_text_checked(text: &str) -> Result<(), String> {
+    set_text(text)?;
+    match get_text()? {
+        Some(current) if current == text => Ok(()),
+        _ => Err("Clipboard contents changed before paste (another app wrote to it)".to_string()),
+    }
+}
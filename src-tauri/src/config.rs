@@ -0,0 +1,241 @@
+// Persisted backend configuration
+// Layout profiles and per-instance overrides, stored as JSON in the app config dir
+
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::PathBuf;
+use std::sync::Mutex;
+
+use crate::budget::BudgetPolicy;
+use crate::detect::DetectionConfig;
+use crate::keymap::KeybindingProfile;
+use crate::layout::LayoutProfile;
+use crate::playbook::Playbook;
+use crate::retry::RetryPolicy;
+use crate::rules::Rule;
+use crate::schedule::Schedule;
+use crate::verify::VerifyPolicy;
+
+/// Per-instance overrides, keyed by project name (same key the frontend uses
+/// for saved backlog configs, since window handles change between sessions)
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct InstanceConfig {
+    /// Name of the layout profile used for detection and chat input
+    pub layout_profile: Option<String>,
+    /// Overrides the global detection config
+    pub detection: Option<DetectionConfig>,
+    /// Overrides the global post-action verification policy
+    pub verify: Option<VerifyPolicy>,
+    /// Overrides the global dry-run setting
+    pub dry_run: Option<bool>,
+    /// Overrides the global retry policy
+    pub retry: Option<RetryPolicy>,
+    /// Run windows and quiet hours, applied on top of the global schedule
+    pub schedule: Option<Schedule>,
+    /// Name of the playbook driving the instance's prompts
+    pub playbook: Option<String>,
+    /// Overrides the global budgets
+    pub budget: Option<BudgetPolicy>,
+    /// Name of the keybinding profile used for editor actions
+    pub keybinding_profile: Option<String>,
+}
+
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct BobConfig {
+    /// User-defined and calibrated profiles (built-in defaults are not stored)
+    pub layout_profiles: Vec<LayoutProfile>,
+    /// User-defined keybinding profiles (built-in defaults are not stored)
+    pub keybinding_profiles: Vec<KeybindingProfile>,
+    pub instances: HashMap<String, InstanceConfig>,
+    pub detection: DetectionConfig,
+    pub verify: VerifyPolicy,
+    pub retry: RetryPolicy,
+    pub schedule: Schedule,
+    pub budget: BudgetPolicy,
+    /// Stop-condition rules, global and per instance
+    pub rules: Vec<Rule>,
+    pub playbooks: Vec<Playbook>,
+    /// Playbook for instances without their own
+    pub default_playbook: Option<String>,
+}
+
+impl BobConfig {
+    /// Resolve the layout profile for an instance, falling back to the default layout
+    pub fn layout_for(&self, project_name: Option<&str>) -> LayoutProfile {
+        let name = project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.layout_profile.as_deref());
+
+        name.and_then(|n| self.find_layout(n))
+            .unwrap_or_else(LayoutProfile::right)
+    }
+
+    /// Detection config for an instance, falling back to the global one
+    pub fn detection_for(&self, project_name: Option<&str>) -> DetectionConfig {
+        project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.detection.clone())
+            .unwrap_or_else(|| self.detection.clone())
+    }
+
+    /// Verification policy for an instance, falling back to the global one
+    pub fn verify_for(&self, project_name: Option<&str>) -> VerifyPolicy {
+        project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.verify.clone())
+            .unwrap_or_else(|| self.verify.clone())
+    }
+
+    /// Retry policy for an instance, falling back to the global one
+    pub fn retry_for(&self, project_name: Option<&str>) -> RetryPolicy {
+        project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.retry.clone())
+            .unwrap_or_else(|| self.retry.clone())
+    }
+
+    /// Schedules an instance must satisfy: the global one and its own, if any
+    pub fn schedules_for(&self, project_name: Option<&str>) -> Vec<Schedule> {
+        let own = project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.schedule.clone());
+        std::iter::once(self.schedule.clone()).chain(own).collect()
+    }
+
+    /// Budgets for an instance, falling back to the global ones
+    pub fn budget_for(&self, project_name: Option<&str>) -> BudgetPolicy {
+        project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.budget.clone())
+            .unwrap_or_else(|| self.budget.clone())
+    }
+
+    /// Playbook for an instance, falling back to the default one (None = auto prompt)
+    pub fn playbook_for(&self, project_name: Option<&str>) -> Option<Playbook> {
+        let name = project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.playbook.as_deref())
+            .or(self.default_playbook.as_deref())?;
+        self.playbooks.iter().find(|p| p.name == name).cloned()
+    }
+
+    /// Whether an instance runs in dry-run, falling back to the global setting
+    pub fn dry_run_for(&self, project_name: Option<&str>, global: bool) -> bool {
+        project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.dry_run)
+            .unwrap_or(global)
+    }
+
+    /// Look up a profile by name, user profiles first, then built-ins
+    pub fn find_layout(&self, name: &str) -> Option<LayoutProfile> {
+        self.layout_profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .or_else(|| {
+                LayoutProfile::builtin()
+                    .into_iter()
+                    .find(|p| p.name == name)
+            })
+    }
+
+    /// Resolve the keybinding profile for an instance, falling back to the default keys
+    pub fn keybindings_for(&self, project_name: Option<&str>) -> KeybindingProfile {
+        let name = project_name
+            .and_then(|p| self.instances.get(p))
+            .and_then(|i| i.keybinding_profile.as_deref());
+
+        name.and_then(|n| self.find_keybindings(n))
+            .unwrap_or_else(KeybindingProfile::antigravity)
+    }
+
+    /// Look up a keybinding profile by name, user profiles first, then built-ins
+    pub fn find_keybindings(&self, name: &str) -> Option<KeybindingProfile> {
+        self.keybinding_profiles
+            .iter()
+            .find(|p| p.name == name)
+            .cloned()
+            .or_else(|| {
+                KeybindingProfile::builtin()
+                    .into_iter()
+                    .find(|p| p.name == name)
+            })
+    }
+
+    /// Insert or replace a user keybinding profile
+    pub fn upsert_keybindings(&mut self, profile: KeybindingProfile) {
+        match self
+            .keybinding_profiles
+            .iter_mut()
+            .find(|p| p.name == profile.name)
+        {
+            Some(existing) => *existing = profile,
+            None => self.keybinding_profiles.push(profile),
+        }
+    }
+
+    /// Insert or replace a user profile
+    pub fn upsert_layout(&mut self, profile: LayoutProfile) {
+        match self
+            .layout_profiles
+            .iter_mut()
+            .find(|p| p.name == profile.name)
+        {
+            Some(existing) => *existing = profile,
+            None => self.layout_profiles.push(profile),
+        }
+    }
+}
+
+/// Config shared with Tauri commands through managed state
+pub struct ConfigStore {
+    path: PathBuf,
+    config: Mutex<BobConfig>,
+}
+
+impl ConfigStore {
+    /// Load config from disk, using defaults if the file is missing or invalid
+    pub fn load(path: PathBuf) -> Self {
+        let config = std::fs::read_to_string(&path)
+            .ok()
+            .and_then(|s| match serde_json::from_str(&s) {
+                Ok(c) => Some(c),
+                Err(e) => {
+                    println!("[config] Ignoring invalid config {:?}: {}", path, e);
+                    None
+                }
+            })
+            .unwrap_or_default();
+
+        ConfigStore {
+            path,
+            config: Mutex::new(config),
+        }
+    }
+
+    /// Snapshot of the current config
+    pub fn get(&self) -> BobConfig {
+        self.config.lock().unwrap().clone()
+    }
+
+    /// Mutate the config and persist it
+    pub fn update<R>(&self, f: impl FnOnce(&mut BobConfig) -> R) -> Result<R, String> {
+        let mut config = self.config.lock().unwrap();
+        let result = f(&mut config);
+        self.save(&config)?;
+        Ok(result)
+    }
+
+    fn save(&self, config: &BobConfig) -> Result<(), String> {
+        if let Some(parent) = self.path.parent() {
+            std::fs::create_dir_all(parent)
+                .map_err(|e| format!("Failed to create config directory: {}", e))?;
+        }
+        let json = serde_json::to_string_pretty(config)
+            .map_err(|e| format!("Failed to serialize config: {}", e))?;
+        std::fs::write(&self.path, json).map_err(|e| format!("Failed to write config: {}", e))
+    }
+}
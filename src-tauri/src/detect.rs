@@ -0,0 +1,691 @@
+// UI state detection by pixel scanning
+// Scan regions come from the instance's layout profile
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::layout::{LayoutProfile, WindowRect};
+
+/// Which UI element a detection refers to
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum ElementKind {
+    Accept,
+    Pause,
+    ChatRed,
+    Retry,
+    Enter,
+}
+
+/// A detected element with how strongly the pixels around it matched
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DetectedElement {
+    pub kind: ElementKind,
+    pub x: i32,
+    pub y: i32,
+    /// matched / sampled pixels in the verification cluster (0.0 - 1.0)
+    pub confidence: f32,
+    pub matched_pixels: u32,
+    pub sampled_pixels: u32,
+}
+
+/// Detection tuning, global or per instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct DetectionConfig {
+    /// Consecutive agreeing frames required before the state counts as stable
+    pub stable_frames: u32,
+    /// Delay between frames when stable_frames > 1
+    pub frame_gap_ms: u64,
+    /// Candidates below this confidence are ignored
+    pub min_confidence: f32,
+}
+
+impl Default for DetectionConfig {
+    fn default() -> Self {
+        DetectionConfig {
+            stable_frames: 1,
+            frame_gap_ms: 150,
+            min_confidence: 0.0,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UIStateResult {
+    #[serde(rename = "hasAcceptButton")]
+    pub has_accept_button: bool,
+    #[serde(rename = "hasEnterButton")]
+    pub has_enter_button: bool,
+    #[serde(rename = "hasRetryButton")]
+    pub has_retry_button: bool,
+    #[serde(rename = "isPaused")]
+    pub is_paused: bool,
+    #[serde(rename = "chatButtonColor", default)]
+    pub chat_button_color: String,
+    #[serde(rename = "acceptButtonX")]
+    pub accept_button_x: i32,
+    #[serde(rename = "acceptButtonY")]
+    pub accept_button_y: i32,
+    #[serde(rename = "enterButtonX")]
+    pub enter_button_x: i32,
+    #[serde(rename = "enterButtonY")]
+    pub enter_button_y: i32,
+    #[serde(rename = "retryButtonX")]
+    pub retry_button_x: i32,
+    #[serde(rename = "retryButtonY")]
+    pub retry_button_y: i32,
+    #[serde(rename = "isBottomButton")]
+    pub is_bottom_button: bool,
+    /// Every element found in the last frame, with confidence
+    #[serde(default)]
+    pub elements: Vec<DetectedElement>,
+    /// True once `agreeingFrames` reached the configured stable_frames
+    #[serde(default)]
+    pub stable: bool,
+    #[serde(rename = "agreeingFrames", default)]
+    pub agreeing_frames: u32,
+    pub error: Option<String>,
+}
+
+impl Default for UIStateResult {
+    fn default() -> Self {
+        UIStateResult {
+            has_accept_button: false,
+            has_enter_button: false,
+            has_retry_button: false,
+            is_paused: false,
+            chat_button_color: String::from("none"),
+            accept_button_x: 0,
+            accept_button_y: 0,
+            enter_button_x: 0,
+            enter_button_y: 0,
+            retry_button_x: 0,
+            retry_button_y: 0,
+            is_bottom_button: false,
+            elements: Vec::new(),
+            stable: false,
+            agreeing_frames: 0,
+            error: None,
+        }
+    }
+}
+
+impl UIStateResult {
+    /// The parts of the state that decide what action gets taken.
+    /// Coordinates are left out so a button that shifts a few pixels still agrees.
+    fn signature(&self) -> (bool, bool, bool, bool, bool, &str) {
+        (
+            self.has_accept_button,
+            self.is_bottom_button,
+            self.has_retry_button,
+            self.has_enter_button,
+            self.is_paused,
+            &self.chat_button_color,
+        )
+    }
+
+    pub fn agrees_with(&self, other: &UIStateResult) -> bool {
+        self.signature() == other.signature()
+    }
+}
+
+/// Anything that can return screen pixels as (r, g, b)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub trait PixelSource {
+    /// None when the pixel can't be read (CLR_INVALID)
+    fn pixel(&self, x: i32, y: i32) -> Option<(u32, u32, u32)>;
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_button_blue((r, g, b): (u32, u32, u32)) -> bool {
+    r < 100 && g >= 100 && b >= 150
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_retry_blue((r, g, b): (u32, u32, u32)) -> bool {
+    r < 100 && g >= 100 && b >= 180
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_pause_red((r, g, b): (u32, u32, u32)) -> bool {
+    r >= 180 && g < 100 && b < 100
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_chat_red((r, g, b): (u32, u32, u32)) -> bool {
+    r >= 150 && g < 100 && b < 100
+}
+
+/// Score a candidate by sampling a grid of offsets around it
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn cluster_score(
+    src: &impl PixelSource,
+    kind: ElementKind,
+    (x, y): (i32, i32),
+    dxs: &[i32],
+    dys: &[i32],
+    matches: impl Fn((u32, u32, u32)) -> bool,
+) -> DetectedElement {
+    let mut matched = 0;
+    let mut sampled = 0;
+    for &dy in dys {
+        for &dx in dxs {
+            sampled += 1;
+            if src.pixel(x + dx, y + dy).is_some_and(&matches) {
+                matched += 1;
+            }
+        }
+    }
+    DetectedElement {
+        kind,
+        x,
+        y,
+        confidence: matched as f32 / sampled.max(1) as f32,
+        matched_pixels: matched,
+        sampled_pixels: sampled,
+    }
+}
+
+/// On non-primary monitors (negative coords), GetPixel returns all-same values.
+/// Sample 10 diverse points. If they're ALL identical, pixel reads are broken.
+/// Returns the repeated value when unreliable.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn check_reliability(src: &impl PixelSource, rect: &WindowRect) -> Option<(u32, u32, u32)> {
+    let test_points: [(f64, f64); 10] = [
+        (0.3, 0.3),
+        (0.5, 0.5),
+        (0.7, 0.3),
+        (0.3, 0.7),
+        (0.9, 0.9),
+        (0.5, 0.8),
+        (0.8, 0.5),
+        (0.6, 0.6),
+        (0.4, 0.9),
+        (0.9, 0.4),
+    ];
+    let mut first_pixel: Option<(u32, u32, u32)> = None;
+    for &(xp, yp) in &test_points {
+        let tx = rect.left + (rect.width() as f64 * xp) as i32;
+        let ty = rect.top + (rect.height() as f64 * yp) as i32;
+        let Some(tp) = src.pixel(tx, ty) else {
+            continue;
+        };
+        match first_pixel {
+            None => first_pixel = Some(tp),
+            Some(fp) if tp != fp => return None,
+            Some(_) => {}
+        }
+    }
+    first_pixel
+}
+
+/// Run all detection passes against a window using the given layout
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn scan(
+    src: &impl PixelSource,
+    rect: &WindowRect,
+    layout: &LayoutProfile,
+    config: &DetectionConfig,
+) -> UIStateResult {
+    let mut result = UIStateResult::default();
+    let width = rect.width();
+    let height = rect.height();
+
+    let step_x = 30;
+    let step_y = 25;
+
+    // ===== PASS 1: Scan for Accept/Run buttons (blue/green/teal) =====
+    let (scan_start_x, scan_start_y, scan_end_x, scan_end_y) =
+        layout.accept_region.to_pixels(width, height);
+
+    let mut y = scan_end_y;
+    'accept_outer: while y > scan_start_y {
+        let mut x = scan_start_x;
+        while x < scan_end_x {
+            let sx = rect.left + x;
+            let sy = rect.top + y;
+
+            // Blue/Green/Teal button, verify neighbor
+            if src.pixel(sx, sy).is_some_and(is_button_blue)
+                && src.pixel(sx + 25, sy).is_some_and(is_button_blue)
+            {
+                let element = cluster_score(
+                    src,
+                    ElementKind::Accept,
+                    (sx + 15, sy),
+                    &[-15, -5, 5, 15, 25, 35],
+                    &[-4, 0, 4],
+                    is_button_blue,
+                );
+                if element.confidence >= config.min_confidence {
+                    result.has_accept_button = true;
+                    result.accept_button_x = element.x;
+                    result.accept_button_y = element.y;
+                    result.is_bottom_button =
+                        y > (height as f64 * layout.bottom_button_threshold) as i32;
+                    result.elements.push(element);
+                    break 'accept_outer;
+                }
+            }
+
+            x += step_x;
+        }
+        y -= step_y;
+    }
+
+    // ===== PASS 2: Check for pause/stop (red square) =====
+    if !result.has_accept_button {
+        let (pause_start_x, pause_start_y, pause_end_x, pause_end_y) =
+            layout.pause_region.to_pixels(width, height);
+
+        let mut y = pause_start_y;
+        'pause_outer: while y < pause_end_y {
+            let mut x = pause_start_x;
+            while x < pause_end_x {
+                let sx = rect.left + x;
+                let sy = rect.top + y;
+
+                // Quick neighbor verify
+                if src.pixel(sx, sy).is_some_and(is_pause_red)
+                    && src.pixel(sx + 5, sy).is_some_and(|(r1, _, _)| r1 >= 180)
+                {
+                    let element = cluster_score(
+                        src,
+                        ElementKind::Pause,
+                        (sx, sy),
+                        &[0, 3, 6, 9],
+                        &[-3, 0, 3],
+                        is_pause_red,
+                    );
+                    if element.confidence >= config.min_confidence {
+                        result.is_paused = true;
+                        result.chat_button_color = "red".to_string();
+                        result.elements.push(element);
+                        break 'pause_outer;
+                    }
+                }
+                x += 12;
+            }
+            y += 12;
+        }
+    }
+
+    // ===== PASS 3: Determine chat state =====
+    if !result.has_accept_button && !result.is_paused {
+        let probe_points = layout.chat_probe_points(rect);
+        let readable = probe_points
+            .iter()
+            .filter(|&&(sx, sy)| src.pixel(sx, sy).is_some())
+            .count();
+
+        let red_element = probe_points
+            .iter()
+            .filter(|&&(sx, sy)| src.pixel(sx, sy).is_some_and(is_chat_red))
+            .map(|&point| {
+                cluster_score(
+                    src,
+                    ElementKind::ChatRed,
+                    point,
+                    &[-4, 0, 4],
+                    &[-4, 0, 4],
+                    is_chat_red,
+                )
+            })
+            .find(|e| e.confidence >= config.min_confidence);
+
+        if let Some(element) = red_element {
+            result.chat_button_color = "red".to_string();
+            result.is_paused = true;
+            result.elements.push(element);
+        } else {
+            result.chat_button_color = "gray".to_string();
+
+            // Check for Retry button with cluster verification
+            let (retry_start_x, retry_start_y, retry_end_x, retry_end_y) =
+                layout.retry_region.to_pixels(width, height);
+
+            let mut ry = retry_end_y;
+            'retry_outer: while ry > retry_start_y {
+                let mut rx = retry_start_x;
+                while rx < retry_end_x {
+                    let px = rect.left + rx;
+                    let py = rect.top + ry;
+
+                    // Blue Retry button
+                    if src.pixel(px, py).is_some_and(|(pr, pg, pb)| {
+                        pr < 100 && (100..=200).contains(&pg) && pb >= 180
+                    }) {
+                        let element = cluster_score(
+                            src,
+                            ElementKind::Retry,
+                            (px, py),
+                            &[0, 10, 20, 30, 40],
+                            &[0],
+                            is_retry_blue,
+                        );
+
+                        if element.matched_pixels >= 3
+                            && element.confidence >= config.min_confidence
+                        {
+                            result.has_retry_button = true;
+                            result.retry_button_x = px + 20;
+                            result.retry_button_y = py;
+                            result.elements.push(DetectedElement {
+                                x: px + 20,
+                                ..element
+                            });
+                            break 'retry_outer;
+                        }
+                    }
+                    rx += 25;
+                }
+                ry -= 20;
+            }
+
+            // If no Retry, chat is ready
+            if !result.has_retry_button {
+                let (ex, ey) = layout.enter_point(rect);
+                result.has_enter_button = true;
+                result.enter_button_x = ex;
+                result.enter_button_y = ey;
+                // Confidence here is how much of the probe grid could be read
+                // and came back not-red
+                result.elements.push(DetectedElement {
+                    kind: ElementKind::Enter,
+                    x: ex,
+                    y: ey,
+                    confidence: readable as f32 / probe_points.len().max(1) as f32,
+                    matched_pixels: readable as u32,
+                    sampled_pixels: probe_points.len() as u32,
+                });
+            }
+        }
+    }
+
+    result
+}
+
+/// Scan repeatedly until `stable_frames` consecutive frames agree, or give up
+/// after twice that many attempts. The last frame is returned with `stable` set.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn scan_stable(
+    src: &impl PixelSource,
+    rect: &WindowRect,
+    layout: &LayoutProfile,
+    config: &DetectionConfig,
+) -> UIStateResult {
+    let required = config.stable_frames.max(1);
+    let mut last = scan(src, rect, layout, config);
+    let mut agreeing = 1;
+
+    for _ in 1..required * 2 {
+        if agreeing >= required {
+            break;
+        }
+        std::thread::sleep(std::time::Duration::from_millis(config.frame_gap_ms));
+        let next = scan(src, rect, layout, config);
+        agreeing = if next.agrees_with(&last) {
+            agreeing + 1
+        } else {
+            1
+        };
+        last = next;
+    }
+
+    last.agreeing_frames = agreeing;
+    last.stable = agreeing >= required;
+    last
+}
+
+/// Get the detection config for an instance (or the global one)
+#[tauri::command]
+pub fn get_detection_config(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> DetectionConfig {
+    config.get().detection_for(project_name.as_deref())
+}
+
+/// Set the global detection config, or an instance override when project_name is given
+#[tauri::command]
+pub fn set_detection_config(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    detection: DetectionConfig,
+) -> Result<(), String> {
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .detection = Some(detection);
+        }
+        None => c.detection = detection,
+    })
+}
+
+/// Bring the window to the foreground and scroll the chat to the bottom
+/// so the buttons are visible. Returns the window rect to scan.
+#[cfg(target_os = "windows")]
+pub fn prepare_window(
+    window_handle: i64,
+    layout: &LayoutProfile,
+    input: &mut dyn crate::input::InputBackend,
+) -> Result<WindowRect, String> {
+    use winapi::shared::windef::HWND;
+    use winapi::um::winuser::{IsIconic, SetProcessDPIAware};
+
+    unsafe {
+        SetProcessDPIAware();
+
+        // Check if minimized
+        if IsIconic(window_handle as HWND) != 0 {
+            return Err("Window is minimized".to_string());
+        }
+    }
+
+    // Get window rect
+    let rect = crate::layout::window_rect(window_handle).ok_or("Failed to get window rect")?;
+
+    if rect.width() <= 0 || rect.height() <= 0 {
+        return Err("Invalid window size".to_string());
+    }
+
+    input.focus(window_handle)?;
+    input.sleep(50);
+
+    // Scroll to bottom (mouse wheel in chat area)
+    let (chat_x, chat_y) = layout.scroll_point.to_screen(&rect);
+    let _ = input.move_to(chat_x, chat_y);
+    input.sleep(30);
+
+    for _ in 0..5 {
+        let _ = input.wheel(-120 * 10); // Scroll down aggressively
+        input.sleep(15);
+    }
+    input.sleep(80);
+
+    Ok(rect)
+}
+
+/// Reads pixels straight from the screen DC
+#[cfg(target_os = "windows")]
+pub struct ScreenPixels {
+    hdc: winapi::shared::windef::HDC,
+}
+
+#[cfg(target_os = "windows")]
+impl ScreenPixels {
+    pub fn new() -> Self {
+        ScreenPixels {
+            hdc: unsafe { winapi::um::winuser::GetDC(std::ptr::null_mut()) },
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl Drop for ScreenPixels {
+    fn drop(&mut self) {
+        unsafe {
+            winapi::um::winuser::ReleaseDC(std::ptr::null_mut(), self.hdc);
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl PixelSource for ScreenPixels {
+    fn pixel(&self, x: i32, y: i32) -> Option<(u32, u32, u32)> {
+        let pixel = unsafe { winapi::um::wingdi::GetPixel(self.hdc, x, y) };
+        if pixel == 0xFFFFFFFF {
+            return None; // CLR_INVALID
+        }
+        Some((pixel & 0xFF, (pixel >> 8) & 0xFF, (pixel >> 16) & 0xFF))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::frame::Frame;
+    use std::cell::Cell;
+
+    const BACKGROUND: (u8, u8, u8) = (40, 40, 40);
+    const BLUE: (u8, u8, u8) = (30, 130, 170);
+
+    fn rect() -> WindowRect {
+        WindowRect {
+            left: 0,
+            top: 0,
+            right: 400,
+            bottom: 400,
+        }
+    }
+
+    fn config(stable_frames: u32, min_confidence: f32) -> DetectionConfig {
+        DetectionConfig {
+            stable_frames,
+            frame_gap_ms: 0,
+            min_confidence,
+        }
+    }
+
+    fn blank() -> Frame {
+        Frame::filled(rect(), BACKGROUND)
+    }
+
+    /// A frame with a blue block of the given width inside the accept region
+    fn with_button(width: i32) -> Frame {
+        let mut frame = blank();
+        for y in 285..300 {
+            for x in 230..230 + width {
+                frame.set(x, y, BLUE);
+            }
+        }
+        frame
+    }
+
+    /// Replays one frame per scan, repeating the last one once they run out.
+    /// Every scan starts at the bottom-left corner of the accept region.
+    struct Frames {
+        frames: Vec<Frame>,
+        start: (i32, i32),
+        scans: Cell<usize>,
+    }
+
+    impl Frames {
+        fn new(frames: Vec<Frame>, layout: &LayoutProfile) -> Self {
+            let r = rect();
+            let (x, _, _, y) = layout.accept_region.to_pixels(r.width(), r.height());
+            Frames {
+                frames,
+                start: (r.left + x, r.top + y),
+                scans: Cell::new(0),
+            }
+        }
+    }
+
+    impl PixelSource for Frames {
+        fn pixel(&self, x: i32, y: i32) -> Option<(u32, u32, u32)> {
+            if (x, y) == self.start {
+                self.scans.set(self.scans.get() + 1);
+            }
+            let i = (self.scans.get().max(1) - 1).min(self.frames.len() - 1);
+            self.frames[i].pixel(x, y)
+        }
+    }
+
+    #[test]
+    fn solid_button_is_detected_with_full_confidence() {
+        let layout = LayoutProfile::right();
+        let result = scan(&with_button(80), &rect(), &layout, &config(1, 0.9));
+
+        assert!(result.has_accept_button);
+        assert_eq!(result.accept_button_y, 292);
+        let element = &result.elements[0];
+        assert_eq!(element.kind, ElementKind::Accept);
+        assert_eq!(element.confidence, 1.0);
+    }
+
+    #[test]
+    fn candidates_below_min_confidence_are_dropped() {
+        let layout = LayoutProfile::right();
+        // Wide enough for the neighbor check but only 4 of 6 cluster columns
+        let frame = with_button(35);
+
+        let loose = scan(&frame, &rect(), &layout, &config(1, 0.5));
+        assert!(loose.has_accept_button);
+        assert!((loose.elements[0].confidence - 4.0 / 6.0).abs() < 1e-6);
+
+        let strict = scan(&frame, &rect(), &layout, &config(1, 0.9));
+        assert!(!strict.has_accept_button);
+        assert!(strict
+            .elements
+            .iter()
+            .all(|e| e.kind != ElementKind::Accept));
+        assert!(strict.has_enter_button);
+    }
+
+    #[test]
+    fn single_noisy_frame_is_not_reported_when_stability_is_required() {
+        let layout = LayoutProfile::right();
+        let src = Frames::new(vec![with_button(80), blank(), blank()], &layout);
+
+        let result = scan_stable(&src, &rect(), &layout, &config(2, 0.0));
+
+        assert_eq!(src.scans.get(), 3);
+        assert!(!result.has_accept_button);
+        assert!(result.stable);
+        assert_eq!(result.agreeing_frames, 2);
+    }
+
+    #[test]
+    fn persistent_button_becomes_stable() {
+        let layout = LayoutProfile::right();
+        let src = Frames::new(vec![with_button(80)], &layout);
+
+        let result = scan_stable(&src, &rect(), &layout, &config(3, 0.0));
+
+        assert_eq!(src.scans.get(), 3);
+        assert!(result.has_accept_button);
+        assert!(result.stable);
+        assert_eq!(result.agreeing_frames, 3);
+    }
+
+    #[test]
+    fn flickering_source_gives_up_unstable() {
+        let layout = LayoutProfile::right();
+        let src = Frames::new(
+            vec![with_button(80), blank(), with_button(80), blank(), blank()],
+            &layout,
+        );
+
+        let result = scan_stable(&src, &rect(), &layout, &config(2, 0.0));
+
+        // Two frames required, so at most four attempts
+        assert_eq!(src.scans.get(), 4);
+        assert!(!result.stable);
+        assert_eq!(result.agreeing_frames, 1);
+    }
+}
@@ -0,0 +1,372 @@
+// Annotated debug captures of detection passes
+// Saves the frame as PNG with the scanned regions and hits drawn on top,
+// plus a JSON sidecar describing each decision, for attaching to bug reports
+
+use serde::Serialize;
+use std::cell::RefCell;
+use std::path::PathBuf;
+use tauri::Manager;
+
+use crate::actions::{self, ActionContext};
+use crate::detect::{DetectionConfig, PixelSource, UIStateResult};
+use crate::frame::{Frame, Rgb};
+use crate::layout::{LayoutProfile, Region, WindowRect};
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const ACCEPT_COLOR: Rgb = (0, 255, 255);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const PAUSE_COLOR: Rgb = (255, 64, 64);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const RETRY_COLOR: Rgb = (64, 128, 255);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const PROBE_COLOR: Rgb = (255, 220, 0);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const SAMPLE_COLOR: Rgb = (200, 200, 200);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const MATCH_COLOR: Rgb = (255, 0, 255);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const ELEMENT_COLOR: Rgb = (0, 255, 0);
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const SCROLL_COLOR: Rgb = (255, 255, 255);
+
+/// A sampled point and the value read there
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub type Sample = (i32, i32, Option<(u32, u32, u32)>);
+
+/// Wraps a pixel source and remembers every point the detector sampled
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub struct RecordingSource<'a, S: PixelSource> {
+    inner: &'a S,
+    samples: RefCell<Vec<Sample>>,
+}
+
+impl<'a, S: PixelSource> RecordingSource<'a, S> {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn new(inner: &'a S) -> Self {
+        RecordingSource {
+            inner,
+            samples: RefCell::new(Vec::new()),
+        }
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn into_samples(self) -> Vec<Sample> {
+        self.samples.into_inner()
+    }
+}
+
+impl<S: PixelSource> PixelSource for RecordingSource<'_, S> {
+    fn pixel(&self, x: i32, y: i32) -> Option<(u32, u32, u32)> {
+        let value = self.inner.pixel(x, y);
+        self.samples.borrow_mut().push((x, y, value));
+        value
+    }
+}
+
+/// Any sample that any pass would treat as part of a button
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn is_match((r, g, b): (u32, u32, u32)) -> bool {
+    let blue = r < 100 && g >= 100 && b >= 150;
+    let red = r >= 150 && g < 100 && b < 100;
+    blue || red
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugReport {
+    pub timestamp: String,
+    pub window_handle: i64,
+    pub project_name: Option<String>,
+    pub rect: WindowRect,
+    pub layout: LayoutProfile,
+    pub detection: DetectionConfig,
+    /// Repeated pixel value when the reliability check failed
+    pub unreliable_pixel: Option<(u32, u32, u32)>,
+    pub result: UIStateResult,
+    pub decisions: Vec<String>,
+    pub sampled_points: usize,
+    pub matched_points: usize,
+    pub elapsed_ms: u128,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DebugCapture {
+    pub image_path: String,
+    pub report_path: String,
+    pub report: DebugReport,
+}
+
+/// The action the polling loop would take for this state
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn planned_action(result: &UIStateResult) -> String {
+    if result.error.is_some() && result.chat_button_color == "none" {
+        return "skip (detection error)".to_string();
+    }
+    if result.has_accept_button && result.is_bottom_button {
+        return format!(
+            "click Accept all at ({}, {})",
+            result.accept_button_x, result.accept_button_y
+        );
+    }
+    let dialog = result.has_accept_button && !result.is_bottom_button;
+    if result.is_paused && result.chat_button_color != "red" {
+        return if dialog {
+            "accept dialog (Alt+Enter)".to_string()
+        } else {
+            "wait (agent working)".to_string()
+        };
+    }
+    match result.chat_button_color.as_str() {
+        "none" => "skip (pixel detection unreliable)".to_string(),
+        "gray" if result.has_retry_button => format!(
+            "click Retry at ({}, {})",
+            result.retry_button_x, result.retry_button_y
+        ),
+        "gray" if result.has_enter_button => "send prompt".to_string(),
+        "gray" => "nothing".to_string(),
+        "red" if dialog => "accept dialog (Alt+Enter)".to_string(),
+        "red" => "wait (agent working)".to_string(),
+        _ if dialog => "accept dialog (Alt+Enter)".to_string(),
+        _ => "scroll to bottom".to_string(),
+    }
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn describe_passes(result: &UIStateResult) -> Vec<String> {
+    let mut decisions = Vec::new();
+
+    for element in &result.elements {
+        decisions.push(format!(
+            "{:?} at ({}, {}) confidence {:.2} ({}/{} pixels)",
+            element.kind,
+            element.x,
+            element.y,
+            element.confidence,
+            element.matched_pixels,
+            element.sampled_pixels
+        ));
+    }
+    if !result.has_accept_button {
+        decisions.push("pass 1: no Accept/Run button in accept region".to_string());
+    } else {
+        decisions.push(format!(
+            "pass 1: Accept is {} button - later passes skipped",
+            if result.is_bottom_button {
+                "bottom (Accept all)"
+            } else {
+                "dialog"
+            }
+        ));
+    }
+    if !result.has_accept_button {
+        decisions.push(format!(
+            "pass 2/3: chat button color {}, paused={}",
+            result.chat_button_color, result.is_paused
+        ));
+    }
+    if result.chat_button_color == "gray" {
+        decisions.push(if result.has_retry_button {
+            "pass 3: Retry button found".to_string()
+        } else {
+            "pass 3: no Retry button - chat ready".to_string()
+        });
+    }
+    decisions.push(format!(
+        "stability: {} agreeing frame(s), stable={}",
+        result.agreeing_frames, result.stable
+    ));
+    if let Some(ref error) = result.error {
+        decisions.push(format!("error: {}", error));
+    }
+    decisions.push(format!("action: {}", planned_action(result)));
+    decisions
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn draw_region(frame: &mut Frame, region: Region, color: Rgb) {
+    let rect = frame.rect;
+    let (x0, y0, x1, y1) = region.to_pixels(rect.width(), rect.height());
+    frame.draw_rect(
+        rect.left + x0,
+        rect.top + y0,
+        rect.left + x1,
+        rect.top + y1,
+        color,
+    );
+}
+
+/// Draw regions, samples and detected elements over the captured frame
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn annotate(
+    frame: &mut Frame,
+    layout: &LayoutProfile,
+    samples: &[Sample],
+    result: &UIStateResult,
+) {
+    let rect = frame.rect;
+
+    draw_region(frame, layout.accept_region, ACCEPT_COLOR);
+    draw_region(frame, layout.pause_region, PAUSE_COLOR);
+    draw_region(frame, layout.retry_region, RETRY_COLOR);
+
+    for (x, y) in layout.chat_probe_points(&rect) {
+        frame.draw_dot(x, y, 2, PROBE_COLOR);
+    }
+
+    let (sx, sy) = layout.scroll_point.to_screen(&rect);
+    frame.draw_cross(sx, sy, 6, SCROLL_COLOR);
+
+    for &(x, y, value) in samples {
+        if value.is_some_and(is_match) {
+            frame.draw_dot(x, y, 2, MATCH_COLOR);
+        } else {
+            frame.set(x, y, SAMPLE_COLOR);
+        }
+    }
+
+    for element in &result.elements {
+        frame.draw_cross(element.x, element.y, 12, ELEMENT_COLOR);
+    }
+}
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn diagnostics_dir(app: &tauri::AppHandle) -> Result<PathBuf, String> {
+    let dir = app
+        .path()
+        .app_data_dir()
+        .map_err(|e| format!("Failed to resolve app data dir: {}", e))?
+        .join("diagnostics");
+    std::fs::create_dir_all(&dir)
+        .map_err(|e| format!("Failed to create diagnostics directory: {}", e))?;
+    Ok(dir)
+}
+
+/// Run a detection pass and save an annotated PNG + JSON report
+#[tauri::command]
+pub async fn debug_detect(
+    app: tauri::AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> Result<DebugCapture, String> {
+    // Waits for a queue turn; keep it off the main thread
+    let handle = app.clone();
+    actions::blocking(app, move |ctx| {
+        capture(&handle, ctx, window_handle, project_name)
+    })
+    .await?
+}
+
+fn capture(
+    app: &tauri::AppHandle,
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> Result<DebugCapture, String> {
+    let start = std::time::Instant::now();
+    let cfg = ctx.config.get();
+    let layout = cfg.layout_for(project_name.as_deref());
+    let detection = cfg.detection_for(project_name.as_deref());
+
+    #[cfg(target_os = "windows")]
+    {
+        use crate::input::{DryRunInput, InputBackend};
+        use crate::scheduler::GuardedInput;
+
+        let dry_run = ctx.dry_run(project_name.as_deref());
+        let (rect, mut frame, unreliable_pixel, result, samples) =
+            ctx.scheduler
+                .run(window_handle, "debug_detect", ctx.timeout(), |token| {
+                    // Same rules as any other action: wait for the user to go
+                    // idle, and in dry runs capture without touching the window
+                    let mut input: Box<dyn InputBackend + '_> = if dry_run {
+                        Box::new(DryRunInput::new(None))
+                    } else {
+                        crate::idle::guard(ctx, window_handle)?;
+                        Box::new(GuardedInput::new(crate::input::native()?, token))
+                    };
+                    let rect = crate::detect::prepare_window(window_handle, &layout, &mut *input)?;
+                    let frame = crate::frame::capture(&rect)?;
+
+                    // Scan the live screen (same source as detect_ui_state) and record samples
+                    let screen = crate::detect::ScreenPixels::new();
+
+                    let recorder = RecordingSource::new(&screen);
+                    let unreliable_pixel = crate::detect::check_reliability(&recorder, &rect);
+                    let result = match unreliable_pixel {
+                        Some(pixel) => UIStateResult {
+                            error: Some(format!(
+                                "GetPixel unreliable at window position L={} T={} (value {:?})",
+                                rect.left, rect.top, pixel
+                            )),
+                            ..UIStateResult::default()
+                        },
+                        None => crate::detect::scan_stable(&recorder, &rect, &layout, &detection),
+                    };
+                    Ok((
+                        rect,
+                        frame,
+                        unreliable_pixel,
+                        result,
+                        recorder.into_samples(),
+                    ))
+                })?;
+
+        annotate(&mut frame, &layout, &samples, &result);
+
+        let stamp = chrono::Local::now().format("%Y%m%d-%H%M%S").to_string();
+        let base = format!(
+            "detect-{}-{}",
+            project_name.as_deref().unwrap_or("window"),
+            stamp
+        )
+        .replace(
+            |c: char| !c.is_ascii_alphanumeric() && c != '-' && c != '_',
+            "_",
+        );
+        let dir = diagnostics_dir(app)?;
+        let image_path = dir.join(format!("{}.png", base));
+        let report_path = dir.join(format!("{}.json", base));
+
+        frame.save_png(&image_path)?;
+
+        let report = DebugReport {
+            timestamp: chrono::Local::now().to_rfc3339(),
+            window_handle,
+            project_name,
+            rect,
+            decisions: describe_passes(&result),
+            sampled_points: samples.len(),
+            matched_points: samples
+                .iter()
+                .filter(|(_, _, v)| v.is_some_and(is_match))
+                .count(),
+            layout,
+            detection,
+            unreliable_pixel,
+            result,
+            elapsed_ms: start.elapsed().as_millis(),
+        };
+
+        let json = serde_json::to_string_pretty(&report)
+            .map_err(|e| format!("Failed to serialize report: {}", e))?;
+        std::fs::write(&report_path, json).map_err(|e| format!("Failed to write report: {}", e))?;
+
+        println!(
+            "[debug_detect] Saved {:?} and {:?}",
+            image_path, report_path
+        );
+
+        Ok(DebugCapture {
+            image_path: image_path.to_string_lossy().to_string(),
+            report_path: report_path.to_string_lossy().to_string(),
+            report,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (app, ctx, window_handle, start, layout, detection);
+        Err("Debug capture only supported on Windows".to_string())
+    }
+}
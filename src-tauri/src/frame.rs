@@ -0,0 +1,197 @@
+// Captured window frames (RGB buffer in screen coordinates)
+
+use crate::detect::PixelSource;
+use crate::layout::WindowRect;
+
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub type Rgb = (u8, u8, u8);
+
+/// An RGB snapshot of a screen rect. Coordinates passed to `pixel`/`set`
+/// are absolute screen coordinates, like the live GetPixel source.
+pub struct Frame {
+    pub rect: WindowRect,
+    pixels: Vec<u8>,
+}
+
+impl Frame {
+    pub fn width(&self) -> i32 {
+        self.rect.width()
+    }
+
+    pub fn height(&self) -> i32 {
+        self.rect.height()
+    }
+
+    /// Raw RGB bytes, row-major, top-down
+    pub fn rgb_bytes(&self) -> &[u8] {
+        &self.pixels
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn offset(&self, x: i32, y: i32) -> Option<usize> {
+        if !self.rect.contains(x, y) {
+            return None;
+        }
+        let rx = (x - self.rect.left) as usize;
+        let ry = (y - self.rect.top) as usize;
+        Some((ry * self.width() as usize + rx) * 3)
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn get(&self, x: i32, y: i32) -> Option<Rgb> {
+        self.offset(x, y)
+            .map(|i| (self.pixels[i], self.pixels[i + 1], self.pixels[i + 2]))
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn set(&mut self, x: i32, y: i32, (r, g, b): Rgb) {
+        if let Some(i) = self.offset(x, y) {
+            self.pixels[i] = r;
+            self.pixels[i + 1] = g;
+            self.pixels[i + 2] = b;
+        }
+    }
+
+    /// 1px rectangle outline (screen coordinates, inclusive)
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn draw_rect(&mut self, left: i32, top: i32, right: i32, bottom: i32, color: Rgb) {
+        for x in left..=right {
+            self.set(x, top, color);
+            self.set(x, bottom, color);
+        }
+        for y in top..=bottom {
+            self.set(left, y, color);
+            self.set(right, y, color);
+        }
+    }
+
+    /// Filled square of side 2*radius+1
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn draw_dot(&mut self, x: i32, y: i32, radius: i32, color: Rgb) {
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                self.set(x + dx, y + dy, color);
+            }
+        }
+    }
+
+    /// Crosshair with a 2px stroke
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn draw_cross(&mut self, x: i32, y: i32, size: i32, color: Rgb) {
+        for d in -size..=size {
+            for w in 0..2 {
+                self.set(x + d, y + w, color);
+                self.set(x + w, y + d, color);
+            }
+        }
+    }
+
+    /// Write the frame as an 8-bit RGB PNG
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn save_png(&self, path: &std::path::Path) -> Result<(), String> {
+        let file =
+            std::fs::File::create(path).map_err(|e| format!("Failed to create PNG: {}", e))?;
+        let mut encoder = png::Encoder::new(
+            std::io::BufWriter::new(file),
+            self.width() as u32,
+            self.height() as u32,
+        );
+        encoder.set_color(png::ColorType::Rgb);
+        encoder.set_depth(png::BitDepth::Eight);
+        let mut writer = encoder
+            .write_header()
+            .map_err(|e| format!("Failed to write PNG header: {}", e))?;
+        writer
+            .write_image_data(&self.pixels)
+            .map_err(|e| format!("Failed to write PNG data: {}", e))
+    }
+}
+
+#[cfg(test)]
+impl Frame {
+    /// A frame of one color
+    pub fn filled(rect: WindowRect, (r, g, b): Rgb) -> Self {
+        let len = (rect.width() * rect.height()).max(0) as usize;
+        Frame {
+            rect,
+            pixels: [r, g, b].repeat(len),
+        }
+    }
+}
+
+impl PixelSource for Frame {
+    fn pixel(&self, x: i32, y: i32) -> Option<(u32, u32, u32)> {
+        self.get(x, y)
+            .map(|(r, g, b)| (r as u32, g as u32, b as u32))
+    }
+}
+
+/// Copy a screen rect into a Frame with BitBlt
+#[cfg(target_os = "windows")]
+pub fn capture(rect: &WindowRect) -> Result<Frame, String> {
+    use winapi::um::wingdi::{
+        BitBlt, CreateCompatibleBitmap, CreateCompatibleDC, DeleteDC, DeleteObject, GetDIBits,
+        SelectObject, BITMAPINFO, BITMAPINFOHEADER, BI_RGB, DIB_RGB_COLORS, SRCCOPY,
+    };
+    use winapi::um::winuser::{GetDC, ReleaseDC};
+
+    let width = rect.width();
+    let height = rect.height();
+    if width <= 0 || height <= 0 {
+        return Err("Invalid window size".to_string());
+    }
+
+    unsafe {
+        let screen_dc = GetDC(std::ptr::null_mut());
+        let mem_dc = CreateCompatibleDC(screen_dc);
+        let bitmap = CreateCompatibleBitmap(screen_dc, width, height);
+        let old = SelectObject(mem_dc, bitmap as _);
+
+        let copied = BitBlt(
+            mem_dc, 0, 0, width, height, screen_dc, rect.left, rect.top, SRCCOPY,
+        );
+
+        let mut info: BITMAPINFO = std::mem::zeroed();
+        info.bmiHeader.biSize = std::mem::size_of::<BITMAPINFOHEADER>() as u32;
+        info.bmiHeader.biWidth = width;
+        info.bmiHeader.biHeight = -height; // top-down rows
+        info.bmiHeader.biPlanes = 1;
+        info.bmiHeader.biBitCount = 32;
+        info.bmiHeader.biCompression = BI_RGB;
+
+        let mut bgra = vec![0u8; (width * height * 4) as usize];
+        SelectObject(mem_dc, old);
+        let lines = GetDIBits(
+            mem_dc,
+            bitmap,
+            0,
+            height as u32,
+            bgra.as_mut_ptr() as _,
+            &mut info,
+            DIB_RGB_COLORS,
+        );
+
+        DeleteObject(bitmap as _);
+        DeleteDC(mem_dc);
+        ReleaseDC(std::ptr::null_mut(), screen_dc);
+
+        if copied == 0 || lines == 0 {
+            return Err("Failed to capture window pixels".to_string());
+        }
+
+        let pixels = bgra
+            .chunks_exact(4)
+            .flat_map(|p| [p[2], p[1], p[0]])
+            .collect();
+
+        Ok(Frame {
+            rect: *rect,
+            pixels,
+        })
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn capture(_rect: &WindowRect) -> Result<Frame, String> {
+    Err("Frame capture only supported on Windows".to_string())
+}
@@ -0,0 +1,150 @@
+// User-activity guard
+// Focus-taking actions wait until the user has left keyboard and mouse alone
+// for a while, so automation doesn't hijack someone working on the machine
+
+use crate::actions::ActionContext;
+
+/// How long since the user last touched keyboard or mouse
+pub trait IdleSource {
+    fn idle_ms(&self) -> u64;
+
+    /// The user is interacting with BOB itself
+    fn user_in_app(&self) -> bool {
+        false
+    }
+}
+
+/// Fixed idle time, for driving the guard without real input
+#[cfg(test)]
+pub struct FixedIdle(pub u64);
+
+#[cfg(test)]
+impl IdleSource for FixedIdle {
+    fn idle_ms(&self) -> u64 {
+        self.0
+    }
+}
+
+/// GetLastInputInfo, ignoring input we injected ourselves
+pub struct SystemIdle;
+
+#[cfg(target_os = "windows")]
+impl IdleSource for SystemIdle {
+    fn idle_ms(&self) -> u64 {
+        use winapi::um::sysinfoapi::GetTickCount;
+        use winapi::um::winuser::{GetLastInputInfo, LASTINPUTINFO};
+
+        unsafe {
+            let mut info = LASTINPUTINFO {
+                cbSize: std::mem::size_of::<LASTINPUTINFO>() as u32,
+                dwTime: 0,
+            };
+            if GetLastInputInfo(&mut info) == 0 {
+                return u64::MAX;
+            }
+            // Our own SendInput also resets the timer; if the latest input
+            // was ours, the user hasn't touched anything since
+            let injected = crate::input::last_injected_tick();
+            if injected != 0 && info.dwTime <= injected.wrapping_add(50) {
+                return u64::MAX;
+            }
+            GetTickCount().wrapping_sub(info.dwTime) as u64
+        }
+    }
+
+    fn user_in_app(&self) -> bool {
+        use winapi::um::winuser::{GetForegroundWindow, GetWindowThreadProcessId};
+
+        unsafe {
+            let mut pid = 0;
+            GetWindowThreadProcessId(GetForegroundWindow(), &mut pid);
+            pid == std::process::id()
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+impl IdleSource for SystemIdle {
+    fn idle_ms(&self) -> u64 {
+        u64::MAX
+    }
+}
+
+/// Ok if the user has been idle for at least `required_seconds` (0 = guard off).
+/// Actions the user asked for from BOB's own window go ahead; the supervisor
+/// still waits, since the user may be typing in BOB's settings.
+pub fn check(
+    source: &dyn IdleSource,
+    required_seconds: u32,
+    user_initiated: bool,
+) -> Result<(), String> {
+    if required_seconds == 0 || (user_initiated && source.user_in_app()) {
+        return Ok(());
+    }
+    let idle = source.idle_ms();
+    let required = required_seconds as u64 * 1000;
+    if idle >= required {
+        return Ok(());
+    }
+    Err(format!(
+        "Waiting for user idle ({}s of {}s)",
+        idle / 1000,
+        required_seconds
+    ))
+}
+
+/// Check the system idle time before taking focus from the user and
+/// record on the instance whether it is waiting
+pub fn guard(ctx: &ActionContext, window_handle: i64) -> Result<(), String> {
+    let result = check(
+        &SystemIdle,
+        ctx.settings.get().user_idle_seconds,
+        ctx.user_initiated,
+    );
+    ctx.runtime
+        .with(window_handle, |rt| rt.waiting_for_idle = result.is_err());
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Idle source with BOB's own window in the foreground
+    struct InApp(u64);
+
+    impl IdleSource for InApp {
+        fn idle_ms(&self) -> u64 {
+            self.0
+        }
+
+        fn user_in_app(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn zero_threshold_disables_guard() {
+        assert!(check(&FixedIdle(0), 0, false).is_ok());
+    }
+
+    #[test]
+    fn waits_below_threshold() {
+        assert_eq!(
+            check(&FixedIdle(2500), 5, false),
+            Err("Waiting for user idle (2s of 5s)".to_string())
+        );
+    }
+
+    #[test]
+    fn passes_at_threshold() {
+        assert!(check(&FixedIdle(5000), 5, false).is_ok());
+        assert!(check(&FixedIdle(4999), 5, false).is_err());
+    }
+
+    #[test]
+    fn only_user_actions_bypass_in_app() {
+        assert!(check(&InApp(0), 5, true).is_ok());
+        assert!(check(&InApp(0), 5, false).is_err());
+    }
+}
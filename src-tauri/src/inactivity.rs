@@ -0,0 +1,44 @@
+// Inactivity timeout
+// An instance with no prompt, no visible chat change and no state change for
+// `inactivity_timeout_minutes` is timed out. With a recovery prompt configured
+// it first gets one nudge; only if that doesn't restart progress is it disabled.
+
+use crate::runtime::InstanceRuntime;
+use crate::settings::Settings;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Active,
+    /// Idle too long; send the recovery prompt once
+    Recover {
+        idle_ms: u64,
+    },
+    /// Idle too long (after the recovery prompt, if any); stop the instance
+    TimedOut {
+        idle_ms: u64,
+    },
+}
+
+/// Latest sign of life: a prompt, a visible chat change or a state change
+pub fn last_activity(rt: &InstanceRuntime) -> u64 {
+    rt.last_prompt_at
+        .max(rt.visual.last_visual_change)
+        .max(rt.state.since)
+}
+
+pub fn check(settings: &Settings, rt: &InstanceRuntime, now: u64) -> Verdict {
+    let limit_ms = settings.inactivity_timeout_minutes as u64 * 60 * 1000;
+    // Nothing to time out before the first prompt
+    if limit_ms == 0 || rt.last_prompt_at == 0 {
+        return Verdict::Active;
+    }
+
+    let idle_ms = now.saturating_sub(last_activity(rt));
+    if idle_ms <= limit_ms {
+        Verdict::Active
+    } else if !settings.inactivity_recovery_prompt.trim().is_empty() && rt.recovery_prompt_at == 0 {
+        Verdict::Recover { idle_ms }
+    } else {
+        Verdict::TimedOut { idle_ms }
+    }
+}
@@ -0,0 +1,558 @@
+// Mouse, keyboard and clipboard input
+// Actions go through the InputBackend trait: SendInput on Windows, or a
+// recorder that keeps the action sequence for inspection
+
+use serde::Serialize;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+use crate::layout::WindowRect;
+
+/// Tick count (GetTickCount) of the last input we injected
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+static LAST_INJECTED_TICK: AtomicU32 = AtomicU32::new(0);
+
+/// When we last sent input, so idle detection can ignore it (0 = never)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn last_injected_tick() -> u32 {
+    LAST_INJECTED_TICK.load(Ordering::Relaxed)
+}
+
+/// Win32 virtual-key code
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+pub struct Key(pub u16);
+
+impl Key {
+    pub const BACKSPACE: Key = Key(0x08);
+    pub const TAB: Key = Key(0x09);
+    pub const ENTER: Key = Key(0x0D);
+    pub const SHIFT: Key = Key(0x10);
+    pub const CONTROL: Key = Key(0x11);
+    pub const ALT: Key = Key(0x12);
+    pub const ESCAPE: Key = Key(0x1B);
+    pub const SPACE: Key = Key(0x20);
+    pub const PAGE_UP: Key = Key(0x21);
+    pub const PAGE_DOWN: Key = Key(0x22);
+    pub const END: Key = Key(0x23);
+    pub const HOME: Key = Key(0x24);
+    pub const LEFT: Key = Key(0x25);
+    pub const UP: Key = Key(0x26);
+    pub const RIGHT: Key = Key(0x27);
+    pub const DOWN: Key = Key(0x28);
+    pub const DELETE: Key = Key(0x2E);
+
+    /// Letter or digit key (virtual-key codes match uppercase ASCII)
+    pub fn char(c: char) -> Key {
+        Key(c.to_ascii_uppercase() as u16)
+    }
+
+    /// F1-F24
+    pub fn function(n: u8) -> Key {
+        Key(0x6F + n as u16)
+    }
+
+    /// Parse a key name as written in keybinding profiles ("Ctrl", "Enter", "F5", "L")
+    pub fn parse(name: &str) -> Option<Key> {
+        let key = match name.to_ascii_lowercase().as_str() {
+            "backspace" => Key::BACKSPACE,
+            "tab" => Key::TAB,
+            "enter" | "return" => Key::ENTER,
+            "shift" => Key::SHIFT,
+            "ctrl" | "control" => Key::CONTROL,
+            "alt" => Key::ALT,
+            "esc" | "escape" => Key::ESCAPE,
+            "space" => Key::SPACE,
+            "pageup" => Key::PAGE_UP,
+            "pagedown" => Key::PAGE_DOWN,
+            "end" => Key::END,
+            "home" => Key::HOME,
+            "left" => Key::LEFT,
+            "up" => Key::UP,
+            "right" => Key::RIGHT,
+            "down" => Key::DOWN,
+            "del" | "delete" => Key::DELETE,
+            other => {
+                let mut chars = other.chars();
+                return match (chars.next(), chars.next()) {
+                    (Some(c), None) if c.is_ascii_alphanumeric() => Some(Key::char(c)),
+                    (Some('f'), Some(_)) => match other[1..].parse::<u8>() {
+                        Ok(n @ 1..=24) => Some(Key::function(n)),
+                        _ => None,
+                    },
+                    _ => None,
+                };
+            }
+        };
+        Some(key)
+    }
+}
+
+impl std::fmt::Display for Key {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match *self {
+            Key::BACKSPACE => "Backspace",
+            Key::TAB => "Tab",
+            Key::ENTER => "Enter",
+            Key::SHIFT => "Shift",
+            Key::CONTROL => "Ctrl",
+            Key::ALT => "Alt",
+            Key::ESCAPE => "Escape",
+            Key::SPACE => "Space",
+            Key::PAGE_UP => "PageUp",
+            Key::PAGE_DOWN => "PageDown",
+            Key::END => "End",
+            Key::HOME => "Home",
+            Key::LEFT => "Left",
+            Key::UP => "Up",
+            Key::RIGHT => "Right",
+            Key::DOWN => "Down",
+            Key::DELETE => "Delete",
+            Key(code @ 0x70..=0x87) => return write!(f, "F{}", code - 0x6F),
+            Key(code) if (code as u8).is_ascii_alphanumeric() && code < 0x80 => {
+                return write!(f, "{}", code as u8 as char);
+            }
+            Key(code) => return write!(f, "VK 0x{:02X}", code),
+        };
+        f.write_str(name)
+    }
+}
+
+/// One input step, as recorded by `RecordingInput`
+#[derive(Debug, Clone, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase", tag = "type")]
+pub enum InputAction {
+    Focus {
+        window_handle: i64,
+    },
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    MoveTo {
+        x: i32,
+        y: i32,
+    },
+    Click {
+        x: i32,
+        y: i32,
+    },
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    Wheel {
+        delta: i32,
+    },
+    Chord {
+        keys: Vec<Key>,
+    },
+    Type {
+        text: String,
+    },
+    Paste {
+        text: String,
+    },
+    Copy,
+}
+
+impl std::fmt::Display for InputAction {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            InputAction::Focus { window_handle } => write!(f, "focus window {}", window_handle),
+            InputAction::MoveTo { x, y } => write!(f, "move to ({}, {})", x, y),
+            InputAction::Click { x, y } => write!(f, "click ({}, {})", x, y),
+            InputAction::Wheel { delta } => write!(f, "wheel {}", delta),
+            InputAction::Chord { keys } => {
+                let names: Vec<String> = keys.iter().map(Key::to_string).collect();
+                write!(f, "keys {}", names.join("+"))
+            }
+            InputAction::Type { text } => write!(f, "type {:?}", text),
+            InputAction::Paste { text } => write!(f, "paste {:?}", text),
+            InputAction::Copy => f.write_str("copy selection"),
+        }
+    }
+}
+
+pub trait InputBackend {
+    /// Bring a window to the foreground, restoring it if minimized.
+    /// Returns whether it actually became the foreground window.
+    fn focus(&mut self, window_handle: i64) -> Result<bool, String>;
+
+    /// Current screen rect of a window
+    fn window_rect(&self, window_handle: i64) -> Option<WindowRect>;
+
+    /// First top-level window whose title contains `title`
+    fn find_window(&self, title: &str) -> Option<i64>;
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), String>;
+
+    /// Left click at a screen position
+    fn click(&mut self, x: i32, y: i32) -> Result<(), String>;
+
+    /// Mouse wheel at the cursor (negative = down, 120 per notch)
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    fn wheel(&mut self, delta: i32) -> Result<(), String>;
+
+    /// Press keys in order and release them in reverse (e.g. Ctrl+Enter)
+    fn key_chord(&mut self, keys: &[Key]) -> Result<(), String>;
+
+    /// Type text as Unicode key events
+    fn type_text(&mut self, text: &str) -> Result<(), String>;
+
+    /// Put text on the clipboard and press Ctrl+V, then restore what the
+    /// clipboard held before
+    fn paste(&mut self, text: &str) -> Result<(), String>;
+
+    /// Press Ctrl+C and return the copied text (None if nothing was copied),
+    /// then restore what the clipboard held before
+    fn copy_selection(&mut self) -> Result<Option<String>, String>;
+
+    /// Wait between steps (no-op for recorders)
+    fn sleep(&mut self, ms: u64);
+}
+
+/// Records actions instead of sending them
+#[derive(Debug, Default)]
+pub struct RecordingInput {
+    pub actions: Vec<InputAction>,
+    /// Rect reported for every window
+    pub rect: Option<WindowRect>,
+    /// Whether `focus` reports success
+    pub focus_ok: bool,
+}
+
+#[cfg(test)]
+impl RecordingInput {
+    pub fn new(rect: WindowRect) -> Self {
+        RecordingInput {
+            actions: Vec::new(),
+            rect: Some(rect),
+            focus_ok: true,
+        }
+    }
+}
+
+impl InputBackend for RecordingInput {
+    fn focus(&mut self, window_handle: i64) -> Result<bool, String> {
+        self.actions.push(InputAction::Focus { window_handle });
+        Ok(self.focus_ok)
+    }
+
+    fn window_rect(&self, _window_handle: i64) -> Option<WindowRect> {
+        self.rect
+    }
+
+    fn find_window(&self, _title: &str) -> Option<i64> {
+        Some(1)
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.actions.push(InputAction::MoveTo { x, y });
+        Ok(())
+    }
+
+    fn click(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.actions.push(InputAction::Click { x, y });
+        Ok(())
+    }
+
+    fn wheel(&mut self, delta: i32) -> Result<(), String> {
+        self.actions.push(InputAction::Wheel { delta });
+        Ok(())
+    }
+
+    fn key_chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        self.actions.push(InputAction::Chord {
+            keys: keys.to_vec(),
+        });
+        Ok(())
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), String> {
+        self.actions.push(InputAction::Type {
+            text: text.to_string(),
+        });
+        Ok(())
+    }
+
+    fn paste(&mut self, text: &str) -> Result<(), String> {
+        self.actions.push(InputAction::Paste {
+            text: text.to_string(),
+        });
+        Ok(())
+    }
+
+    fn copy_selection(&mut self) -> Result<Option<String>, String> {
+        self.actions.push(InputAction::Copy);
+        Ok(None)
+    }
+
+    fn sleep(&mut self, _ms: u64) {}
+}
+
+/// Dry-run backend: records steps as planned actions without sending them.
+/// Window lookups still query the real backend so planned coordinates match
+/// what would have been clicked.
+pub struct DryRunInput {
+    queries: Option<Box<dyn InputBackend>>,
+    recorder: RecordingInput,
+}
+
+impl DryRunInput {
+    pub fn new(queries: Option<Box<dyn InputBackend>>) -> Self {
+        DryRunInput {
+            queries,
+            recorder: RecordingInput {
+                focus_ok: true,
+                ..RecordingInput::default()
+            },
+        }
+    }
+
+    pub fn into_planned(self) -> Vec<InputAction> {
+        self.recorder.actions
+    }
+}
+
+impl InputBackend for DryRunInput {
+    fn focus(&mut self, window_handle: i64) -> Result<bool, String> {
+        self.recorder.focus(window_handle)
+    }
+
+    fn window_rect(&self, window_handle: i64) -> Option<WindowRect> {
+        self.queries.as_ref()?.window_rect(window_handle)
+    }
+
+    fn find_window(&self, title: &str) -> Option<i64> {
+        self.queries.as_ref()?.find_window(title)
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.recorder.move_to(x, y)
+    }
+
+    fn click(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.recorder.click(x, y)
+    }
+
+    fn wheel(&mut self, delta: i32) -> Result<(), String> {
+        self.recorder.wheel(delta)
+    }
+
+    fn key_chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        self.recorder.key_chord(keys)
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), String> {
+        self.recorder.type_text(text)
+    }
+
+    fn paste(&mut self, text: &str) -> Result<(), String> {
+        self.recorder.paste(text)
+    }
+
+    fn copy_selection(&mut self) -> Result<Option<String>, String> {
+        self.recorder.copy_selection()
+    }
+
+    fn sleep(&mut self, _ms: u64) {}
+}
+
+/// SendInput-based backend
+#[cfg(target_os = "windows")]
+pub struct SendInputBackend;
+
+#[cfg(target_os = "windows")]
+impl SendInputBackend {
+    fn send(inputs: &mut [winapi::um::winuser::INPUT]) -> Result<(), String> {
+        use winapi::um::winuser::{SendInput, INPUT};
+
+        let sent = unsafe {
+            SendInput(
+                inputs.len() as u32,
+                inputs.as_mut_ptr(),
+                std::mem::size_of::<INPUT>() as i32,
+            )
+        };
+        LAST_INJECTED_TICK.store(
+            unsafe { winapi::um::sysinfoapi::GetTickCount() },
+            Ordering::Relaxed,
+        );
+        if sent as usize != inputs.len() {
+            return Err("SendInput was blocked (UIPI or secure desktop)".to_string());
+        }
+        Ok(())
+    }
+
+    fn mouse(flags: u32, data: u32) -> winapi::um::winuser::INPUT {
+        use winapi::um::winuser::{INPUT, INPUT_MOUSE};
+
+        unsafe {
+            let mut input: INPUT = std::mem::zeroed();
+            input.type_ = INPUT_MOUSE;
+            let mi = input.u.mi_mut();
+            mi.dwFlags = flags;
+            mi.mouseData = data;
+            input
+        }
+    }
+
+    fn key(vk: u16, scan: u16, flags: u32) -> winapi::um::winuser::INPUT {
+        use winapi::um::winuser::{INPUT, INPUT_KEYBOARD};
+
+        unsafe {
+            let mut input: INPUT = std::mem::zeroed();
+            input.type_ = INPUT_KEYBOARD;
+            let ki = input.u.ki_mut();
+            ki.wVk = vk;
+            ki.wScan = scan;
+            ki.dwFlags = flags;
+            input
+        }
+    }
+}
+
+#[cfg(target_os = "windows")]
+impl InputBackend for SendInputBackend {
+    fn focus(&mut self, window_handle: i64) -> Result<bool, String> {
+        use winapi::shared::windef::HWND;
+        use winapi::um::winuser::{
+            GetForegroundWindow, IsIconic, IsWindow, SetForegroundWindow, ShowWindow, SW_RESTORE,
+        };
+
+        let hwnd = window_handle as HWND;
+        unsafe {
+            if IsWindow(hwnd) == 0 {
+                return Err("Window not found".to_string());
+            }
+            // Only restore if minimized - avoid un-snapping from split layouts
+            if IsIconic(hwnd) != 0 {
+                ShowWindow(hwnd, SW_RESTORE);
+                self.sleep(100);
+            }
+            SetForegroundWindow(hwnd);
+            self.sleep(200);
+            Ok(GetForegroundWindow() == hwnd)
+        }
+    }
+
+    fn window_rect(&self, window_handle: i64) -> Option<WindowRect> {
+        crate::layout::window_rect(window_handle)
+    }
+
+    fn find_window(&self, title: &str) -> Option<i64> {
+        use winapi::shared::minwindef::{BOOL, LPARAM};
+        use winapi::shared::windef::HWND;
+        use winapi::um::winuser::{EnumWindows, GetWindowTextW, IsWindowVisible};
+
+        struct Search {
+            needle: String,
+            found: Option<i64>,
+        }
+
+        unsafe extern "system" fn visit(hwnd: HWND, lparam: LPARAM) -> BOOL {
+            let search = &mut *(lparam as *mut Search);
+            if IsWindowVisible(hwnd) == 0 {
+                return 1;
+            }
+            let mut buf = [0u16; 512];
+            let len = GetWindowTextW(hwnd, buf.as_mut_ptr(), buf.len() as i32);
+            if len > 0 && String::from_utf16_lossy(&buf[..len as usize]).contains(&search.needle) {
+                search.found = Some(hwnd as i64);
+                return 0;
+            }
+            1
+        }
+
+        let mut search = Search {
+            needle: title.to_string(),
+            found: None,
+        };
+        unsafe {
+            EnumWindows(Some(visit), &mut search as *mut Search as LPARAM);
+        }
+        search.found
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), String> {
+        if unsafe { winapi::um::winuser::SetCursorPos(x, y) } == 0 {
+            return Err(format!("Failed to move cursor to ({}, {})", x, y));
+        }
+        Ok(())
+    }
+
+    fn click(&mut self, x: i32, y: i32) -> Result<(), String> {
+        use winapi::um::winuser::{MOUSEEVENTF_LEFTDOWN, MOUSEEVENTF_LEFTUP};
+
+        self.move_to(x, y)?;
+        self.sleep(50);
+        Self::send(&mut [Self::mouse(MOUSEEVENTF_LEFTDOWN, 0)])?;
+        self.sleep(30);
+        Self::send(&mut [Self::mouse(MOUSEEVENTF_LEFTUP, 0)])
+    }
+
+    fn wheel(&mut self, delta: i32) -> Result<(), String> {
+        Self::send(&mut [Self::mouse(
+            winapi::um::winuser::MOUSEEVENTF_WHEEL,
+            delta as u32,
+        )])
+    }
+
+    fn key_chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        use winapi::um::winuser::KEYEVENTF_KEYUP;
+
+        for key in keys {
+            Self::send(&mut [Self::key(key.0, 0, 0)])?;
+            self.sleep(30);
+        }
+        for key in keys.iter().rev() {
+            Self::send(&mut [Self::key(key.0, 0, KEYEVENTF_KEYUP)])?;
+            self.sleep(30);
+        }
+        Ok(())
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), String> {
+        use winapi::um::winuser::{KEYEVENTF_KEYUP, KEYEVENTF_UNICODE};
+
+        for unit in text.encode_utf16() {
+            Self::send(&mut [
+                Self::key(0, unit, KEYEVENTF_UNICODE),
+                Self::key(0, unit, KEYEVENTF_UNICODE | KEYEVENTF_KEYUP),
+            ])?;
+            self.sleep(5);
+        }
+        Ok(())
+    }
+
+    fn paste(&mut self, text: &str) -> Result<(), String> {
+        let _saved = crate::clipboard::ClipboardGuard::save()?;
+        crate::clipboard::set_text_checked(text)?;
+        self.sleep(100);
+        let result = self.key_chord(&[Key::CONTROL, Key::char('v')]);
+
+        // Give the target time to read the clipboard before the guard puts the user's data back
+        self.sleep(150);
+        result
+    }
+
+    fn copy_selection(&mut self) -> Result<Option<String>, String> {
+        let _saved = crate::clipboard::ClipboardGuard::save()?;
+
+        // Marker so a failed copy isn't mistaken for the old clipboard contents
+        let marker = format!("bob-copy-{}", crate::runtime::now_ms());
+        crate::clipboard::set_text(&marker)?;
+        self.key_chord(&[Key::CONTROL, Key::char('c')])?;
+        self.sleep(150);
+        Ok(crate::clipboard::get_text()?.filter(|text| *text != marker))
+    }
+
+    fn sleep(&mut self, ms: u64) {
+        std::thread::sleep(std::time::Duration::from_millis(ms));
+    }
+}
+
+/// The real input backend for this platform
+#[cfg(target_os = "windows")]
+pub fn native() -> Result<Box<dyn InputBackend>, String> {
+    Ok(Box::new(SendInputBackend))
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn native() -> Result<Box<dyn InputBackend>, String> {
+    Err("Input automation only supported on Windows".to_string())
+}
@@ -0,0 +1,184 @@
+// Keybinding profiles - which key chords trigger editor actions
+// Editors and user keymaps differ, so actions resolve chords by meaning
+// (accept, submit, new chat...) instead of hard-coding them
+
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::input::Key;
+
+/// Name of the profile used when an instance has none assigned
+pub const DEFAULT_KEYBINDINGS: &str = "antigravity";
+
+/// Keys pressed together, written as "Ctrl+Shift+L"
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Chord(pub Vec<Key>);
+
+impl Chord {
+    pub fn parse(text: &str) -> Result<Chord, String> {
+        let keys = text
+            .split('+')
+            .map(|part| {
+                let part = part.trim();
+                Key::parse(part).ok_or_else(|| format!("Unknown key \"{}\" in \"{}\"", part, text))
+            })
+            .collect::<Result<Vec<Key>, String>>()?;
+        if keys.is_empty() {
+            return Err("Empty key chord".to_string());
+        }
+        Ok(Chord(keys))
+    }
+
+    pub fn keys(&self) -> &[Key] {
+        &self.0
+    }
+}
+
+impl std::fmt::Display for Chord {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let names: Vec<String> = self.0.iter().map(Key::to_string).collect();
+        f.write_str(&names.join("+"))
+    }
+}
+
+impl Serialize for Chord {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&self.to_string())
+    }
+}
+
+impl<'de> Deserialize<'de> for Chord {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let text = String::deserialize(deserializer)?;
+        Chord::parse(&text).map_err(serde::de::Error::custom)
+    }
+}
+
+fn default_accept_suggestion() -> Chord {
+    Chord(vec![Key::TAB])
+}
+
+/// Semantic editor actions that can be bound to a chord
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum EditorAction {
+    FocusChat,
+    Submit,
+    AcceptSuggestion,
+    Accept,
+    Reject,
+    ScrollBottom,
+    NewChat,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct KeybindingProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Move focus to the chat input (None = click the chat input instead)
+    #[serde(default)]
+    pub focus_chat: Option<Chord>,
+    /// Send the typed prompt
+    pub submit: Chord,
+    /// Take the highlighted entry of the slash-command popup
+    #[serde(default = "default_accept_suggestion")]
+    pub accept_suggestion: Chord,
+    /// Accept the pending dialog / changes
+    pub accept: Chord,
+    /// Reject the pending dialog / changes
+    #[serde(default)]
+    pub reject: Option<Chord>,
+    /// Scroll the chat to the latest message
+    pub scroll_bottom: Chord,
+    /// Start a fresh chat / conversation
+    #[serde(default)]
+    pub new_chat: Option<Chord>,
+}
+
+impl KeybindingProfile {
+    /// Antigravity agent panel (the original hard-coded keys)
+    pub fn antigravity() -> Self {
+        KeybindingProfile {
+            name: DEFAULT_KEYBINDINGS.to_string(),
+            description: "Antigravity agent panel".to_string(),
+            focus_chat: None,
+            submit: Chord(vec![Key::ENTER]),
+            accept_suggestion: default_accept_suggestion(),
+            accept: Chord(vec![Key::ALT, Key::ENTER]),
+            reject: None,
+            scroll_bottom: Chord(vec![Key::CONTROL, Key::END]),
+            new_chat: None,
+        }
+    }
+
+    pub fn builtin() -> Vec<Self> {
+        vec![Self::antigravity()]
+    }
+
+    /// Chord bound to an action, if any
+    pub fn chord(&self, action: EditorAction) -> Option<&Chord> {
+        match action {
+            EditorAction::FocusChat => self.focus_chat.as_ref(),
+            EditorAction::Submit => Some(&self.submit),
+            EditorAction::AcceptSuggestion => Some(&self.accept_suggestion),
+            EditorAction::Accept => Some(&self.accept),
+            EditorAction::Reject => self.reject.as_ref(),
+            EditorAction::ScrollBottom => Some(&self.scroll_bottom),
+            EditorAction::NewChat => self.new_chat.as_ref(),
+        }
+    }
+
+    /// Chord for an action, or an error naming the profile that lacks it
+    pub fn require(&self, action: EditorAction) -> Result<&Chord, String> {
+        self.chord(action).ok_or_else(|| {
+            format!(
+                "Keybinding profile \"{}\" has no binding for {:?}",
+                self.name, action
+            )
+        })
+    }
+}
+
+/// Built-in and user keybinding profiles (user profiles override built-ins)
+#[tauri::command]
+pub fn get_keybinding_profiles(config: tauri::State<'_, ConfigStore>) -> Vec<KeybindingProfile> {
+    let cfg = config.get();
+    let mut profiles: Vec<KeybindingProfile> = KeybindingProfile::builtin()
+        .into_iter()
+        .filter(|b| !cfg.keybinding_profiles.iter().any(|p| p.name == b.name))
+        .collect();
+    profiles.extend(cfg.keybinding_profiles);
+    profiles
+}
+
+/// Create or replace a user keybinding profile
+#[tauri::command]
+pub fn save_keybinding_profile(
+    config: tauri::State<'_, ConfigStore>,
+    profile: KeybindingProfile,
+) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Keybinding profile name cannot be empty".to_string());
+    }
+    config.update(|c| c.upsert_keybindings(profile))
+}
+
+/// Assign a keybinding profile to an instance
+#[tauri::command]
+pub fn set_instance_keybindings(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: String,
+    profile_name: String,
+) -> Result<(), String> {
+    if config.get().find_keybindings(&profile_name).is_none() {
+        return Err(format!("Unknown keybinding profile: {}", profile_name));
+    }
+    config.update(|c| {
+        c.instances
+            .entry(project_name)
+            .or_insert_with(InstanceConfig::default)
+            .keybinding_profile = Some(profile_name);
+    })
+}
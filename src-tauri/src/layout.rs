@@ -0,0 +1,507 @@
+// Layout profiles - where the agent panel lives inside an editor window
+// All regions and points are fractions of the window size (0.0 - 1.0)
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{BobConfig, ConfigStore, InstanceConfig};
+
+/// Name of the profile used when an instance has none assigned
+pub const DEFAULT_LAYOUT: &str = "right";
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Point {
+    pub x: f64,
+    pub y: f64,
+}
+
+impl Point {
+    /// Convert to screen coordinates inside the given window rect
+    pub fn to_screen(self, rect: &WindowRect) -> (i32, i32) {
+        (
+            rect.left + (rect.width() as f64 * self.x) as i32,
+            rect.top + (rect.height() as f64 * self.y) as i32,
+        )
+    }
+}
+
+/// Rectangle expressed as window fractions
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct Region {
+    pub left: f64,
+    pub top: f64,
+    pub right: f64,
+    pub bottom: f64,
+}
+
+impl Region {
+    const fn new(left: f64, top: f64, right: f64, bottom: f64) -> Self {
+        Region {
+            left,
+            top,
+            right,
+            bottom,
+        }
+    }
+
+    /// Window-relative pixel bounds (start_x, start_y, end_x, end_y)
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn to_pixels(self, width: i32, height: i32) -> (i32, i32, i32, i32) {
+        (
+            (width as f64 * self.left) as i32,
+            (height as f64 * self.top) as i32,
+            (width as f64 * self.right) as i32,
+            (height as f64 * self.bottom) as i32,
+        )
+    }
+}
+
+/// Screen rect of the monitored window
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+pub struct WindowRect {
+    pub left: i32,
+    pub top: i32,
+    pub right: i32,
+    pub bottom: i32,
+}
+
+impl WindowRect {
+    pub fn width(&self) -> i32 {
+        self.right - self.left
+    }
+
+    pub fn height(&self) -> i32 {
+        self.bottom - self.top
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn contains(&self, x: i32, y: i32) -> bool {
+        x >= self.left && x < self.right && y >= self.top && y < self.bottom
+    }
+}
+
+/// Sample grid for the chat send/stop button.
+/// Offsets are in pixels, measured left and up from the anchor point.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChatProbe {
+    pub anchor: Point,
+    pub x_offsets: Vec<i32>,
+    pub y_offsets: Vec<i32>,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutProfile {
+    pub name: String,
+    #[serde(default)]
+    pub description: String,
+    /// Accept/Run buttons (blue/green/teal)
+    pub accept_region: Region,
+    /// Accept buttons below this fraction of the height are "Accept all" (click),
+    /// above it they are dialogs (Alt+Enter)
+    pub bottom_button_threshold: f64,
+    /// Red stop square while the agent is working
+    pub pause_region: Region,
+    /// Blue Retry button after an error
+    pub retry_region: Region,
+    pub chat_probe: ChatProbe,
+    /// Where the mouse wheel is sent to scroll the chat to the bottom
+    pub scroll_point: Point,
+    /// Calibrated chat input position (None = script default)
+    #[serde(default)]
+    pub chat_input: Option<Point>,
+    /// Calibrated send button position (None = probe anchor offsets)
+    #[serde(default)]
+    pub send_button: Option<Point>,
+    /// Chat transcript area, hashed for stall detection (None = accept region)
+    #[serde(default)]
+    pub chat_region: Option<Region>,
+}
+
+impl LayoutProfile {
+    /// Agent panel docked on the right (original hard-coded layout)
+    pub fn right() -> Self {
+        LayoutProfile {
+            name: DEFAULT_LAYOUT.to_string(),
+            description: "Agent panel docked on the right".to_string(),
+            accept_region: Region::new(0.50, 0.15, 0.98, 0.98),
+            bottom_button_threshold: 0.65,
+            pause_region: Region::new(0.80, 0.82, 0.97, 0.97),
+            retry_region: Region::new(0.55, 0.55, 0.95, 0.95),
+            chat_probe: ChatProbe {
+                anchor: Point { x: 1.0, y: 1.0 },
+                x_offsets: vec![30, 50, 80, 120, 160, 200, 250],
+                y_offsets: vec![30, 50, 70, 100, 130, 160],
+            },
+            scroll_point: Point { x: 0.88, y: 0.5 },
+            chat_input: None,
+            send_button: None,
+            chat_region: Some(Region::new(0.52, 0.10, 0.98, 0.85)),
+        }
+    }
+
+    /// Agent panel docked on the left
+    pub fn left() -> Self {
+        LayoutProfile {
+            name: "left".to_string(),
+            description: "Agent panel docked on the left".to_string(),
+            accept_region: Region::new(0.02, 0.15, 0.50, 0.98),
+            bottom_button_threshold: 0.65,
+            pause_region: Region::new(0.20, 0.82, 0.45, 0.97),
+            retry_region: Region::new(0.05, 0.55, 0.45, 0.95),
+            chat_probe: ChatProbe {
+                anchor: Point { x: 0.45, y: 1.0 },
+                x_offsets: vec![30, 50, 80, 120, 160, 200, 250],
+                y_offsets: vec![30, 50, 70, 100, 130, 160],
+            },
+            scroll_point: Point { x: 0.20, y: 0.5 },
+            chat_input: Some(Point { x: 0.22, y: 0.92 }),
+            send_button: None,
+            chat_region: Some(Region::new(0.02, 0.10, 0.48, 0.85)),
+        }
+    }
+
+    /// Agent panel docked at the bottom
+    pub fn bottom() -> Self {
+        LayoutProfile {
+            name: "bottom".to_string(),
+            description: "Agent panel docked at the bottom".to_string(),
+            accept_region: Region::new(0.02, 0.50, 0.98, 0.98),
+            bottom_button_threshold: 0.85,
+            pause_region: Region::new(0.80, 0.90, 0.98, 0.99),
+            retry_region: Region::new(0.05, 0.55, 0.95, 0.95),
+            chat_probe: ChatProbe {
+                anchor: Point { x: 1.0, y: 1.0 },
+                x_offsets: vec![30, 50, 80, 120, 160, 200, 250],
+                y_offsets: vec![20, 30, 40, 50, 60],
+            },
+            scroll_point: Point { x: 0.5, y: 0.75 },
+            chat_input: Some(Point { x: 0.5, y: 0.96 }),
+            send_button: None,
+            chat_region: Some(Region::new(0.02, 0.55, 0.98, 0.90)),
+        }
+    }
+
+    /// Agent panel in its own window
+    pub fn detached() -> Self {
+        LayoutProfile {
+            name: "detached".to_string(),
+            description: "Agent panel in a separate window".to_string(),
+            accept_region: Region::new(0.02, 0.05, 0.98, 0.98),
+            bottom_button_threshold: 0.65,
+            pause_region: Region::new(0.60, 0.80, 0.98, 0.98),
+            retry_region: Region::new(0.05, 0.40, 0.95, 0.95),
+            chat_probe: ChatProbe {
+                anchor: Point { x: 1.0, y: 1.0 },
+                x_offsets: vec![30, 50, 80, 120, 160, 200, 250],
+                y_offsets: vec![30, 50, 70, 100, 130, 160],
+            },
+            scroll_point: Point { x: 0.5, y: 0.5 },
+            chat_input: Some(Point { x: 0.5, y: 0.93 }),
+            send_button: None,
+            chat_region: Some(Region::new(0.02, 0.05, 0.98, 0.85)),
+        }
+    }
+
+    pub fn builtin() -> Vec<LayoutProfile> {
+        vec![
+            Self::right(),
+            Self::left(),
+            Self::bottom(),
+            Self::detached(),
+        ]
+    }
+
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn is_builtin(name: &str) -> bool {
+        Self::builtin().iter().any(|p| p.name == name)
+    }
+
+    /// Screen points sampled to classify the chat button color
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn chat_probe_points(&self, rect: &WindowRect) -> Vec<(i32, i32)> {
+        if let Some(send) = self.send_button {
+            // Calibrated: small grid centered on the send button
+            let (cx, cy) = send.to_screen(rect);
+            let deltas = [-10, -5, 0, 5, 10];
+            return deltas
+                .iter()
+                .flat_map(|&dx| deltas.iter().map(move |&dy| (cx + dx, cy + dy)))
+                .collect();
+        }
+
+        let (ax, ay) = self.chat_probe.anchor.to_screen(rect);
+        self.chat_probe
+            .x_offsets
+            .iter()
+            .flat_map(|&xo| {
+                self.chat_probe
+                    .y_offsets
+                    .iter()
+                    .map(move |&yo| (ax - xo, ay - yo))
+            })
+            .collect()
+    }
+
+    /// Region hashed to track visual changes in the chat
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn chat_area(&self) -> Region {
+        self.chat_region.unwrap_or(self.accept_region)
+    }
+
+    /// Where to click to submit when the chat is ready
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn enter_point(&self, rect: &WindowRect) -> (i32, i32) {
+        match self.send_button {
+            Some(send) => send.to_screen(rect),
+            None => {
+                let (ax, ay) = self.chat_probe.anchor.to_screen(rect);
+                (ax - 60, ay - 50)
+            }
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum CalibrationTarget {
+    ChatInput,
+    SendButton,
+}
+
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CalibrationResult {
+    pub profile: LayoutProfile,
+    pub screen_x: i32,
+    pub screen_y: i32,
+    pub point: Point,
+}
+
+/// List built-in and user layout profiles
+#[tauri::command]
+pub fn get_layout_profiles(config: tauri::State<'_, ConfigStore>) -> Vec<LayoutProfile> {
+    let cfg = config.get();
+    let mut profiles: Vec<LayoutProfile> = LayoutProfile::builtin()
+        .into_iter()
+        .filter(|b| !cfg.layout_profiles.iter().any(|p| p.name == b.name))
+        .collect();
+    profiles.extend(cfg.layout_profiles);
+    profiles
+}
+
+/// Create or replace a user layout profile
+#[tauri::command]
+pub fn save_layout_profile(
+    config: tauri::State<'_, ConfigStore>,
+    profile: LayoutProfile,
+) -> Result<(), String> {
+    if profile.name.trim().is_empty() {
+        return Err("Layout profile name cannot be empty".to_string());
+    }
+    config.update(|c| c.upsert_layout(profile))
+}
+
+/// Assign a layout profile to an instance
+#[tauri::command]
+pub fn set_instance_layout(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: String,
+    profile_name: String,
+) -> Result<(), String> {
+    if config.get().find_layout(&profile_name).is_none() {
+        return Err(format!("Unknown layout profile: {}", profile_name));
+    }
+    config.update(|c| {
+        c.instances
+            .entry(project_name)
+            .or_insert_with(InstanceConfig::default)
+            .layout_profile = Some(profile_name);
+    })
+}
+
+/// Get the window rect in screen coordinates
+#[cfg(target_os = "windows")]
+pub fn window_rect(window_handle: i64) -> Option<WindowRect> {
+    use winapi::shared::windef::{HWND, RECT};
+    use winapi::um::winuser::GetWindowRect;
+
+    unsafe {
+        let mut rect: RECT = std::mem::zeroed();
+        if GetWindowRect(window_handle as HWND, &mut rect) == 0 {
+            return None;
+        }
+        Some(WindowRect {
+            left: rect.left,
+            top: rect.top,
+            right: rect.right,
+            bottom: rect.bottom,
+        })
+    }
+}
+
+#[cfg(target_os = "windows")]
+fn cursor_pos() -> Option<(i32, i32)> {
+    use winapi::shared::windef::POINT;
+    use winapi::um::winuser::GetCursorPos;
+
+    unsafe {
+        let mut pt: POINT = std::mem::zeroed();
+        if GetCursorPos(&mut pt) == 0 {
+            return None;
+        }
+        Some((pt.x, pt.y))
+    }
+}
+
+/// Record a calibrated point into the instance's own profile. Any other
+/// profile (built-in or shared with other instances) is copied to a
+/// per-project profile first, so calibrating one instance never moves the
+/// points of another.
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+fn calibrate(
+    c: &mut BobConfig,
+    project_name: &str,
+    target: CalibrationTarget,
+    point: Point,
+) -> LayoutProfile {
+    let mut profile = c.layout_for(Some(project_name));
+    if profile.name != project_name {
+        profile.description = format!("Calibrated from '{}'", profile.name);
+        profile.name = project_name.to_string();
+    }
+    match target {
+        CalibrationTarget::ChatInput => profile.chat_input = Some(point),
+        CalibrationTarget::SendButton => profile.send_button = Some(point),
+    }
+    c.upsert_layout(profile.clone());
+    c.instances
+        .entry(project_name.to_string())
+        .or_default()
+        .layout_profile = Some(profile.name.clone());
+    profile
+}
+
+/// Interactive calibration: the user hovers the mouse over the chat input or
+/// send button, and after `delay_ms` the cursor position is recorded into the
+/// instance's own profile (see `calibrate`).
+#[tauri::command]
+pub async fn calibrate_layout(
+    config: tauri::State<'_, ConfigStore>,
+    window_handle: i64,
+    project_name: String,
+    target: CalibrationTarget,
+    delay_ms: Option<u64>,
+) -> Result<CalibrationResult, String> {
+    tokio::time::sleep(std::time::Duration::from_millis(delay_ms.unwrap_or(3000))).await;
+
+    #[cfg(target_os = "windows")]
+    {
+        unsafe {
+            winapi::um::winuser::SetProcessDPIAware();
+        }
+        let rect = window_rect(window_handle).ok_or("Failed to get window rect")?;
+        let (screen_x, screen_y) = cursor_pos().ok_or("Failed to read cursor position")?;
+
+        if rect.width() <= 0 || rect.height() <= 0 {
+            return Err("Invalid window size".to_string());
+        }
+        if !rect.contains(screen_x, screen_y) {
+            return Err(format!(
+                "Cursor ({}, {}) is outside the window (L={} T={} R={} B={})",
+                screen_x, screen_y, rect.left, rect.top, rect.right, rect.bottom
+            ));
+        }
+
+        let point = Point {
+            x: (screen_x - rect.left) as f64 / rect.width() as f64,
+            y: (screen_y - rect.top) as f64 / rect.height() as f64,
+        };
+
+        let profile = config.update(|c| calibrate(c, &project_name, target, point))?;
+
+        println!(
+            "[calibrate_layout] {:?} for {} at ({}, {}) -> ({:.3}, {:.3})",
+            target, project_name, screen_x, screen_y, point.x, point.y
+        );
+
+        Ok(CalibrationResult {
+            profile,
+            screen_x,
+            screen_y,
+            point,
+        })
+    }
+
+    #[cfg(not(target_os = "windows"))]
+    {
+        let _ = (config, window_handle, project_name, target);
+        Err("Layout calibration only supported on Windows".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const POINT: Point = Point { x: 0.4, y: 0.9 };
+
+    fn assign(c: &mut BobConfig, project_name: &str, profile: &str) {
+        c.instances
+            .entry(project_name.to_string())
+            .or_default()
+            .layout_profile = Some(profile.to_string());
+    }
+
+    #[test]
+    fn builtin_profile_is_copied_per_instance() {
+        let mut c = BobConfig::default();
+
+        let profile = calibrate(&mut c, "alpha", CalibrationTarget::ChatInput, POINT);
+
+        assert_eq!(profile.name, "alpha");
+        assert_eq!(profile.description, "Calibrated from 'right'");
+        assert_eq!(c.layout_for(Some("alpha")).chat_input.unwrap().x, 0.4);
+        assert!(c.layout_for(Some("beta")).chat_input.is_none());
+    }
+
+    #[test]
+    fn shared_user_profile_is_left_alone() {
+        let mut c = BobConfig::default();
+        c.upsert_layout(LayoutProfile {
+            name: "wide".to_string(),
+            ..LayoutProfile::right()
+        });
+        assign(&mut c, "alpha", "wide");
+        assign(&mut c, "beta", "wide");
+
+        calibrate(&mut c, "alpha", CalibrationTarget::SendButton, POINT);
+
+        let shared = c.find_layout("wide").unwrap();
+        assert!(shared.send_button.is_none());
+        assert_eq!(c.layout_for(Some("beta")).name, "wide");
+        let own = c.layout_for(Some("alpha"));
+        assert_eq!(own.name, "alpha");
+        assert_eq!(own.description, "Calibrated from 'wide'");
+        assert_eq!(own.send_button.unwrap().y, 0.9);
+    }
+
+    #[test]
+    fn own_profile_is_updated_in_place() {
+        let mut c = BobConfig::default();
+        calibrate(&mut c, "alpha", CalibrationTarget::ChatInput, POINT);
+
+        calibrate(
+            &mut c,
+            "alpha",
+            CalibrationTarget::SendButton,
+            Point { x: 0.5, y: 0.5 },
+        );
+
+        assert_eq!(c.layout_profiles.len(), 1);
+        let own = c.layout_for(Some("alpha"));
+        assert_eq!(own.description, "Calibrated from 'right'");
+        assert_eq!(own.chat_input.unwrap().x, 0.4);
+        assert_eq!(own.send_button.unwrap().x, 0.5);
+    }
+}
@@ -0,0 +1,282 @@
+// Per-instance lifecycle state machine
+// Every status change goes through `StateMachine::transition`, which checks it
+// against the allowed transitions and records it with its reason, so the
+// history explains how an instance ended up in error, blocked or disabled
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::budget;
+use crate::retry;
+use crate::runtime::{now_ms, Runtime};
+
+/// Transitions kept per instance
+const HISTORY_LEN: usize = 100;
+
+/// Same values as `InstanceStatus` in src/lib/types.ts
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub enum InstanceState {
+    /// Enabled and waiting for the supervisor
+    #[default]
+    Idle,
+    /// A prompt was sent or an action taken; the agent is on it
+    Working,
+    /// Working, but the chat hasn't visibly changed for the stall timeout
+    Stalled,
+    /// Gave up after repeated failures or inactivity
+    Error,
+    /// Backlog finished
+    Complete,
+    /// A stop condition needs a human decision
+    Blocked,
+    /// Held by a rule; re-enable to resume
+    Paused,
+    /// No prompt or chat change for the inactivity timeout
+    TimedOut,
+    /// Outside its run windows or in quiet hours; resumes at the next window
+    OffHours,
+    /// Turned off by the user
+    Disabled,
+}
+
+impl InstanceState {
+    /// Whether the machine may move from `self` to `to`. Finished, blocked,
+    /// paused, timed-out and disabled instances must be re-enabled (back to
+    /// idle) before they work again.
+    pub fn can_transition_to(self, to: InstanceState) -> bool {
+        use InstanceState::*;
+        match (self, to) {
+            (Idle, Stalled) => false,
+            (Idle, _) => true,
+            (Working | Stalled, _) => true,
+            (Error, Idle | Working | Disabled) => true,
+            (OffHours, Idle | Disabled) => true,
+            (Blocked | Complete | Paused | TimedOut, Idle | Disabled) => true,
+            (Disabled, Idle) => true,
+            _ => false,
+        }
+    }
+}
+
+/// One recorded state change
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Transition {
+    pub from: InstanceState,
+    pub to: InstanceState,
+    pub reason: String,
+    pub at: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StateMachine {
+    pub state: InstanceState,
+    /// When the current state was entered (0 = never left the initial idle)
+    pub since: u64,
+    pub history: VecDeque<Transition>,
+}
+
+impl StateMachine {
+    /// Move to `to`, recording why. Staying in the same state is a no-op
+    /// (Ok(false)); a transition the guards don't allow is an error.
+    pub fn transition(&mut self, to: InstanceState, reason: &str) -> Result<bool, String> {
+        if self.state == to {
+            return Ok(false);
+        }
+        if !self.state.can_transition_to(to) {
+            return Err(format!(
+                "Transition {:?} -> {:?} not allowed ({})",
+                self.state, to, reason
+            ));
+        }
+
+        let at = now_ms();
+        if self.history.len() == HISTORY_LEN {
+            self.history.pop_front();
+        }
+        self.history.push_back(Transition {
+            from: self.state,
+            to,
+            reason: reason.to_string(),
+            at,
+        });
+        self.state = to;
+        self.since = at;
+        Ok(true)
+    }
+}
+
+/// Apply a transition to an instance and log it; rejected transitions are
+/// logged and leave the state unchanged. Returns the resulting state.
+pub fn apply(
+    runtime: &Runtime,
+    window_handle: i64,
+    to: InstanceState,
+    reason: &str,
+) -> InstanceState {
+    runtime.with(window_handle, |rt| {
+        let name = rt.project_name.clone().unwrap_or_else(|| "window".into());
+        match rt.state.transition(to, reason) {
+            Ok(true) => println!("[lifecycle] [{}] -> {:?}: {}", name, to, reason),
+            Ok(false) => {}
+            Err(e) => println!("[lifecycle] [{}] {}", name, e),
+        }
+        rt.state.state
+    })
+}
+
+/// State transition history for an instance, oldest first
+#[tauri::command]
+pub fn get_state_history(runtime: tauri::State<'_, Runtime>, window_handle: i64) -> StateMachine {
+    runtime
+        .get(window_handle)
+        .map(|rt| rt.state)
+        .unwrap_or_default()
+}
+
+/// Enable (back to idle) or disable an instance from the dashboard
+#[tauri::command]
+pub fn set_instance_enabled(
+    runtime: tauri::State<'_, Runtime>,
+    window_handle: i64,
+    project_name: Option<String>,
+    enabled: bool,
+) -> InstanceState {
+    set_enabled(&runtime, window_handle, project_name, enabled)
+}
+
+fn set_enabled(
+    runtime: &Runtime,
+    window_handle: i64,
+    project_name: Option<String>,
+    enabled: bool,
+) -> InstanceState {
+    runtime.with(window_handle, |rt| {
+        if project_name.is_some() {
+            rt.project_name = project_name;
+        }
+        // Re-enabling by hand also restores the retry and per-issue budgets
+        if enabled {
+            retry::reset(rt);
+            budget::reset_issue(rt, now_ms());
+        }
+    });
+    let (to, reason) = match enabled {
+        true => (InstanceState::Idle, "Enabled by user"),
+        false => (InstanceState::Disabled, "Disabled by user"),
+    };
+    apply(runtime, window_handle, to, reason)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use InstanceState::*;
+
+    #[test]
+    fn guards_allow_and_reject() {
+        for (from, to) in [
+            (Idle, Working),
+            (Idle, Disabled),
+            (Working, Stalled),
+            (Stalled, Working),
+            (Working, Complete),
+            (Error, Working),
+            (OffHours, Idle),
+            (Paused, Idle),
+            (TimedOut, Disabled),
+            (Disabled, Idle),
+        ] {
+            assert!(from.can_transition_to(to), "{:?} -> {:?}", from, to);
+        }
+        for (from, to) in [
+            (Idle, Stalled),
+            (Paused, Working),
+            (Blocked, Working),
+            (Complete, Working),
+            (TimedOut, Working),
+            (OffHours, Working),
+            (Disabled, Working),
+            (Disabled, Stalled),
+            (Error, Stalled),
+        ] {
+            assert!(!from.can_transition_to(to), "{:?} -> {:?}", from, to);
+        }
+    }
+
+    #[test]
+    fn transition_records_history() {
+        let mut machine = StateMachine::default();
+        assert_eq!(machine.transition(Working, "prompt sent"), Ok(true));
+        assert_eq!(machine.transition(Working, "again"), Ok(false));
+        assert_eq!(machine.state, Working);
+        assert_eq!(machine.history.len(), 1);
+        let entry = &machine.history[0];
+        assert_eq!((entry.from, entry.to), (Idle, Working));
+        assert_eq!(entry.reason, "prompt sent");
+        assert_eq!(machine.since, entry.at);
+    }
+
+    #[test]
+    fn rejected_transition_changes_nothing() {
+        let mut machine = StateMachine::default();
+        machine.transition(Paused, "rule").unwrap();
+        let since = machine.since;
+
+        let error = machine.transition(Working, "prompt sent").unwrap_err();
+        assert_eq!(
+            error,
+            "Transition Paused -> Working not allowed (prompt sent)"
+        );
+        assert_eq!(machine.state, Paused);
+        assert_eq!(machine.since, since);
+        assert_eq!(machine.history.len(), 1);
+    }
+
+    #[test]
+    fn history_is_capped() {
+        let mut machine = StateMachine::default();
+        for i in 0..HISTORY_LEN + 10 {
+            let to = if i % 2 == 0 { Working } else { Idle };
+            machine.transition(to, &i.to_string()).unwrap();
+        }
+        assert_eq!(machine.history.len(), HISTORY_LEN);
+        assert_eq!(machine.history[0].reason, "10");
+        assert_eq!(
+            machine.history.back().unwrap().reason,
+            (HISTORY_LEN + 9).to_string()
+        );
+    }
+
+    #[test]
+    fn enabling_resets_retry_and_issue_budget() {
+        let runtime = Runtime::default();
+        runtime.with(1, |rt| {
+            rt.retry_count = 4;
+            retry::record(&retry::RetryPolicy::default(), rt, 1000);
+            rt.budget.issue_steps = 12;
+            rt.budget.issue_started_at = 1000;
+            rt.state.transition(Working, "prompt sent").unwrap();
+            rt.state.transition(TimedOut, "inactive").unwrap();
+        });
+
+        assert_eq!(
+            set_enabled(&runtime, 1, Some("bob".to_string()), true),
+            Idle
+        );
+        let rt = runtime.get(1).unwrap();
+        assert_eq!(rt.retry_count, 0);
+        assert!(rt.retry.attempts.is_empty());
+        assert_eq!(rt.budget.issue_steps, 0);
+        assert!(rt.budget.issue_started_at > 1000);
+        assert_eq!(rt.project_name.as_deref(), Some("bob"));
+
+        assert_eq!(set_enabled(&runtime, 1, None, false), Disabled);
+        assert_eq!(
+            runtime.get(1).unwrap().state.history.back().unwrap().reason,
+            "Disabled by user"
+        );
+    }
+}
@@ -0,0 +1,109 @@
+// Notifications and file logging shared by commands and backend monitors
+
+use std::path::PathBuf;
+
+use crate::settings::Settings;
+
+/// Post an embed to a Discord webhook
+pub async fn send_discord(webhook_url: &str, title: &str, message: &str) -> Result<(), String> {
+    let client = reqwest::Client::new();
+
+    let payload = serde_json::json!({
+        "embeds": [{
+            "title": title,
+            "description": message,
+            "color": 5814783, // Cyan color
+            "footer": {
+                "text": "BOB Monitor"
+            },
+            "timestamp": chrono::Utc::now().to_rfc3339()
+        }]
+    });
+
+    client
+        .post(webhook_url)
+        .json(&payload)
+        .send()
+        .await
+        .map_err(|e| format!("Failed to send Discord notification: {}", e))?;
+
+    Ok(())
+}
+
+/// Send a Discord notification in the background if a webhook is configured.
+/// In dry-run the notification is only written to the log.
+pub fn spawn_discord(settings: &Settings, dry_run: bool, title: String, message: String) {
+    if dry_run {
+        log_planned_discord(settings, &title, &message);
+        return;
+    }
+    if settings.discord_webhook.is_empty() {
+        return;
+    }
+    let webhook = settings.discord_webhook.clone();
+    tauri::async_runtime::spawn(async move {
+        if let Err(e) = send_discord(&webhook, &title, &message).await {
+            println!("[notify] {}", e);
+        }
+    });
+}
+
+/// Append a log entry to file
+pub fn append_log(log_path: &str, level: &str, message: &str) -> Result<(), String> {
+    use std::fs::{create_dir_all, OpenOptions};
+    use std::io::Write;
+
+    // Use provided path or default to exe directory
+    let path = if log_path.is_empty() {
+        std::env::current_exe()
+            .ok()
+            .and_then(|p| p.parent().map(|d| d.join("bob.log")))
+            .unwrap_or_else(|| PathBuf::from("bob.log"))
+    } else {
+        PathBuf::from(log_path)
+    };
+
+    // Create parent directory if it doesn't exist
+    if let Some(parent) = path.parent() {
+        if !parent.exists() {
+            create_dir_all(parent).map_err(|e| format!("Failed to create log directory: {}", e))?;
+        }
+    }
+
+    // Get current timestamp
+    let timestamp = chrono::Local::now().format("%Y-%m-%d %H:%M:%S%.3f");
+
+    // Format log entry
+    let log_entry = format!("[{}] [{}] {}\n", timestamp, level.to_uppercase(), message);
+
+    // Append to file
+    let mut file = OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(&path)
+        .map_err(|e| format!("Failed to open log file: {}", e))?;
+
+    file.write_all(log_entry.as_bytes())
+        .map_err(|e| format!("Failed to write to log file: {}", e))?;
+
+    Ok(())
+}
+
+/// Log from the backend, honoring the frontend logging settings
+pub fn log(settings: &Settings, level: &str, message: &str) {
+    println!("[{}] {}", level, message);
+    if settings.logging_enabled {
+        if let Err(e) = append_log(&settings.log_file_path, level, message) {
+            println!("[notify] {}", e);
+        }
+    }
+}
+
+/// Log a notification that dry-run kept from reaching Discord
+pub fn log_planned_discord(settings: &Settings, title: &str, message: &str) {
+    log(
+        settings,
+        "DRYRUN",
+        &format!("Discord (not sent): {} - {}", title, message),
+    );
+}
@@ -0,0 +1,405 @@
+// Multi-step playbooks
+// A playbook replaces the single auto prompt with ordered steps per issue
+// (e.g. plan → implement → test → review → mark done). The supervisor sends
+// the current step's prompt whenever the chat is ready and moves to the next
+// step once its advance condition holds; a new current issue starts over.
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::runtime::InstanceRuntime;
+
+/// When a step is done and the playbook moves on
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AdvanceCondition {
+    /// The agent went idle after the step's prompt
+    AgentIdle,
+    /// The backlog changed (an issue was checked off) since the step started
+    BacklogChanged,
+    /// The agent's last response contains the phrase (case-insensitive)
+    StopPhrase { text: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybookStep {
+    pub name: String,
+    /// Prompt template sent while the step is current
+    pub prompt: String,
+    pub advance: AdvanceCondition,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Playbook {
+    pub name: String,
+    pub steps: Vec<PlaybookStep>,
+}
+
+impl Playbook {
+    /// Check that the playbook is named and every step has a prompt
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Playbook name cannot be empty".to_string());
+        }
+        if self.steps.is_empty() {
+            return Err(format!("Playbook \"{}\" has no steps", self.name));
+        }
+        for step in &self.steps {
+            if step.prompt.trim().is_empty() {
+                return Err(format!(
+                    "Playbook \"{}\": step \"{}\" has no prompt",
+                    self.name, step.name
+                ));
+            }
+            if let AdvanceCondition::StopPhrase { ref text } = step.advance {
+                if text.trim().is_empty() {
+                    return Err(format!(
+                        "Playbook \"{}\": step \"{}\" has an empty stop phrase",
+                        self.name, step.name
+                    ));
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Where an instance is in its playbook
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlaybookProgress {
+    pub playbook: String,
+    /// Index of the current step
+    pub step: usize,
+    pub step_name: String,
+    pub total_steps: usize,
+    /// Issue the playbook is working on
+    pub issue: String,
+    /// Completed issues when the step started (for BacklogChanged)
+    pub completed_at_start: i32,
+    /// The current step's prompt has been sent
+    pub prompted: bool,
+    pub step_started_at: u64,
+}
+
+fn backlog_state(rt: &InstanceRuntime) -> (String, i32) {
+    rt.backlog
+        .as_ref()
+        .map(|b| (b.current_issue.clone(), b.completed_issues))
+        .unwrap_or_default()
+}
+
+fn met(condition: &AdvanceCondition, progress: &PlaybookProgress, rt: &InstanceRuntime) -> bool {
+    match condition {
+        AdvanceCondition::AgentIdle => true,
+        AdvanceCondition::BacklogChanged => {
+            let (issue, completed) = backlog_state(rt);
+            completed != progress.completed_at_start || issue != progress.issue
+        }
+        AdvanceCondition::StopPhrase { text } => rt
+            .last_response
+            .as_deref()
+            .is_some_and(|r| r.to_lowercase().contains(&text.to_lowercase())),
+    }
+}
+
+fn start_step(
+    progress: &mut PlaybookProgress,
+    playbook: &Playbook,
+    step: usize,
+    rt: &InstanceRuntime,
+) {
+    let (issue, completed) = backlog_state(rt);
+    progress.step = step;
+    progress.step_name = playbook.steps[step].name.clone();
+    progress.total_steps = playbook.steps.len();
+    progress.issue = issue;
+    progress.completed_at_start = completed;
+    progress.prompted = false;
+    progress.step_started_at = crate::runtime::now_ms();
+}
+
+/// The chat is ready: advance the instance's playbook as far as its
+/// conditions allow and return the prompt of the step to send. The step
+/// stays current (and its prompt is sent again) until its condition holds;
+/// call `mark_sent` once the prompt has actually reached the chat.
+pub fn next_prompt(playbook: &Playbook, rt: &mut InstanceRuntime) -> String {
+    let mut progress = rt.playbook.take().unwrap_or_default();
+    let (issue, _) = backlog_state(rt);
+
+    if progress.playbook != playbook.name || progress.step >= playbook.steps.len() {
+        // New or edited playbook
+        progress.playbook = playbook.name.clone();
+        start_step(&mut progress, playbook, 0, rt);
+    } else if progress.prompted && issue != progress.issue {
+        // The backlog moved on to another issue: start over for it
+        println!(
+            "[playbook] [{}] Issue changed at step \"{}\", restarting",
+            progress.playbook, progress.step_name
+        );
+        start_step(&mut progress, playbook, 0, rt);
+    } else if progress.prompted && met(&playbook.steps[progress.step].advance, &progress, rt) {
+        let next = (progress.step + 1) % playbook.steps.len();
+        start_step(&mut progress, playbook, next, rt);
+    }
+
+    let prompt = playbook.steps[progress.step].prompt.clone();
+    rt.playbook = Some(progress);
+    prompt
+}
+
+/// The current step's prompt reached the chat, so its advance condition
+/// is checked from the next cycle on
+pub fn mark_sent(rt: &mut InstanceRuntime) {
+    if let Some(ref mut progress) = rt.playbook {
+        progress.prompted = true;
+    }
+}
+
+/// Configured playbooks
+#[tauri::command]
+pub fn get_playbooks(config: tauri::State<'_, ConfigStore>) -> Vec<Playbook> {
+    config.get().playbooks
+}
+
+/// Replace the configured playbooks
+#[tauri::command]
+pub fn save_playbooks(
+    config: tauri::State<'_, ConfigStore>,
+    playbooks: Vec<Playbook>,
+) -> Result<(), String> {
+    for playbook in &playbooks {
+        playbook.validate()?;
+    }
+    config.update(|c| c.playbooks = playbooks)
+}
+
+/// Name of the playbook an instance uses (or the global default)
+#[tauri::command]
+pub fn get_instance_playbook(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> Option<String> {
+    config
+        .get()
+        .playbook_for(project_name.as_deref())
+        .map(|p| p.name)
+}
+
+/// Set the global default playbook (None = plain auto prompt), or an
+/// instance's when project_name is given (None = the global default)
+#[tauri::command]
+pub fn set_instance_playbook(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    playbook: Option<String>,
+) -> Result<(), String> {
+    if let Some(ref name) = playbook {
+        if !config.get().playbooks.iter().any(|p| &p.name == name) {
+            return Err(format!("Unknown playbook: {}", name));
+        }
+    }
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .playbook = playbook;
+        }
+        None => c.default_playbook = playbook,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::BacklogResult;
+
+    fn step(name: &str, advance: AdvanceCondition) -> PlaybookStep {
+        PlaybookStep {
+            name: name.to_string(),
+            prompt: format!("{} prompt", name),
+            advance,
+        }
+    }
+
+    fn playbook() -> Playbook {
+        Playbook {
+            name: "issue".to_string(),
+            steps: vec![
+                step("plan", AdvanceCondition::AgentIdle),
+                step("implement", AdvanceCondition::BacklogChanged),
+                step(
+                    "review",
+                    AdvanceCondition::StopPhrase {
+                        text: "LGTM".to_string(),
+                    },
+                ),
+            ],
+        }
+    }
+
+    fn set_backlog(rt: &mut InstanceRuntime, issue: &str, completed: i32) {
+        rt.backlog = Some(BacklogResult {
+            current_issue: issue.to_string(),
+            completed_issues: completed,
+            ..BacklogResult::default()
+        });
+    }
+
+    /// Send the current step's prompt and return it
+    fn send(playbook: &Playbook, rt: &mut InstanceRuntime) -> String {
+        let prompt = next_prompt(playbook, rt);
+        mark_sent(rt);
+        prompt
+    }
+
+    fn step_index(rt: &InstanceRuntime) -> usize {
+        rt.playbook.as_ref().unwrap().step
+    }
+
+    #[test]
+    fn validate_accepts_a_complete_playbook() {
+        assert!(playbook().validate().is_ok());
+    }
+
+    #[test]
+    fn validate_rejects_empty_and_malformed_playbooks() {
+        let unnamed = Playbook {
+            name: " ".to_string(),
+            ..playbook()
+        };
+        assert!(unnamed.validate().unwrap_err().contains("name"));
+
+        let empty = Playbook {
+            steps: Vec::new(),
+            ..playbook()
+        };
+        assert!(empty.validate().unwrap_err().contains("no steps"));
+
+        let mut no_prompt = playbook();
+        no_prompt.steps[1].prompt = "  ".to_string();
+        assert!(no_prompt
+            .validate()
+            .unwrap_err()
+            .contains("\"implement\" has no prompt"));
+
+        let mut no_phrase = playbook();
+        no_phrase.steps[2].advance = AdvanceCondition::StopPhrase {
+            text: String::new(),
+        };
+        assert!(no_phrase.validate().unwrap_err().contains("stop phrase"));
+    }
+
+    #[test]
+    fn first_prompt_starts_at_step_zero_and_repeats_until_sent() {
+        let playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        set_backlog(&mut rt, "#1", 0);
+
+        assert_eq!(next_prompt(&playbook, &mut rt), "plan prompt");
+        // Not marked as sent, so AgentIdle can't advance yet
+        assert_eq!(next_prompt(&playbook, &mut rt), "plan prompt");
+
+        let progress = rt.playbook.as_ref().unwrap();
+        assert_eq!(progress.total_steps, 3);
+        assert_eq!(progress.issue, "#1");
+        assert!(!progress.prompted);
+    }
+
+    #[test]
+    fn agent_idle_advances_after_the_prompt_was_sent() {
+        let playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        set_backlog(&mut rt, "#1", 0);
+
+        send(&playbook, &mut rt);
+        assert_eq!(next_prompt(&playbook, &mut rt), "implement prompt");
+        assert_eq!(step_index(&rt), 1);
+        assert!(!rt.playbook.as_ref().unwrap().prompted);
+    }
+
+    #[test]
+    fn backlog_changed_waits_for_a_completed_issue() {
+        let playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        set_backlog(&mut rt, "#1", 2);
+        send(&playbook, &mut rt);
+        send(&playbook, &mut rt);
+        assert_eq!(step_index(&rt), 1);
+
+        // Same backlog: the step stays current and its prompt is sent again
+        assert_eq!(send(&playbook, &mut rt), "implement prompt");
+        assert_eq!(step_index(&rt), 1);
+
+        set_backlog(&mut rt, "#1", 3);
+        assert_eq!(send(&playbook, &mut rt), "review prompt");
+        assert_eq!(rt.playbook.as_ref().unwrap().completed_at_start, 3);
+    }
+
+    #[test]
+    fn stop_phrase_matches_case_insensitively() {
+        let playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        set_backlog(&mut rt, "#1", 0);
+        send(&playbook, &mut rt);
+        send(&playbook, &mut rt);
+        set_backlog(&mut rt, "#1", 1);
+        send(&playbook, &mut rt);
+        assert_eq!(step_index(&rt), 2);
+
+        rt.last_response = Some("Still a few things to fix".to_string());
+        assert_eq!(send(&playbook, &mut rt), "review prompt");
+
+        rt.last_response = Some("All good, lgtm.".to_string());
+        assert_eq!(next_prompt(&playbook, &mut rt), "plan prompt");
+    }
+
+    #[test]
+    fn last_step_wraps_to_the_first() {
+        let playbook = Playbook {
+            name: "loop".to_string(),
+            steps: vec![
+                step("a", AdvanceCondition::AgentIdle),
+                step("b", AdvanceCondition::AgentIdle),
+            ],
+        };
+        let mut rt = InstanceRuntime::default();
+
+        let sent: Vec<String> = (0..5).map(|_| send(&playbook, &mut rt)).collect();
+        assert_eq!(
+            sent,
+            ["a prompt", "b prompt", "a prompt", "b prompt", "a prompt"]
+        );
+    }
+
+    #[test]
+    fn new_issue_restarts_from_the_first_step() {
+        let playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        set_backlog(&mut rt, "#1", 0);
+        send(&playbook, &mut rt);
+        send(&playbook, &mut rt);
+        assert_eq!(step_index(&rt), 1);
+
+        set_backlog(&mut rt, "#2", 1);
+        assert_eq!(next_prompt(&playbook, &mut rt), "plan prompt");
+        assert_eq!(rt.playbook.as_ref().unwrap().issue, "#2");
+    }
+
+    #[test]
+    fn edited_playbook_starts_over() {
+        let mut playbook = playbook();
+        let mut rt = InstanceRuntime::default();
+        send(&playbook, &mut rt);
+        send(&playbook, &mut rt);
+        assert_eq!(step_index(&rt), 1);
+
+        // The step index no longer exists
+        playbook.steps.truncate(1);
+        assert_eq!(next_prompt(&playbook, &mut rt), "plan prompt");
+        assert_eq!(rt.playbook.as_ref().unwrap().total_steps, 1);
+    }
+}
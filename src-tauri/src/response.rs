@@ -0,0 +1,262 @@
+// Reading the agent's last response and matching stop conditions
+// The chat transcript is copied with select-all + copy inside the chat panel;
+// the user's clipboard is restored afterwards
+
+use serde::Serialize;
+
+use crate::actions::{self, ActionContext};
+use crate::idle;
+use crate::input::{self, InputBackend, Key};
+use crate::layout::LayoutProfile;
+use crate::scheduler::GuardedInput;
+
+/// Longest response kept (the tail of very long answers is what matters)
+const MAX_RESPONSE_CHARS: usize = 4000;
+
+/// Same shape as `StopCondition` in src/lib/types.ts
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct StopCondition {
+    pub detected: bool,
+    pub condition: String,
+    pub message: String,
+}
+
+/// Text after the last prompt we sent. Empty when none of the prompts is in
+/// the transcript, so old chat history never counts as the last response.
+pub fn extract_last_response(transcript: &str, prompts: &[&str]) -> String {
+    let Some(start) = prompts
+        .iter()
+        .filter(|p| !p.trim().is_empty())
+        .filter_map(|p| transcript.rfind(p.trim()).map(|i| i + p.trim().len()))
+        .max()
+    else {
+        return String::new();
+    };
+
+    let response = transcript[start..].trim();
+    let chars = response.chars().count();
+    if chars > MAX_RESPONSE_CHARS {
+        response.chars().skip(chars - MAX_RESPONSE_CHARS).collect()
+    } else {
+        response.to_string()
+    }
+}
+
+/// First stop condition found in the text; the message is the line it appears on
+pub fn evaluate(text: &str, conditions: &[String]) -> StopCondition {
+    for condition in conditions.iter().filter(|c| !c.is_empty()) {
+        if let Some(line) = text.lines().find(|l| l.contains(condition.as_str())) {
+            return StopCondition {
+                detected: true,
+                condition: condition.clone(),
+                message: line.trim().chars().take(200).collect(),
+            };
+        }
+    }
+    StopCondition::default()
+}
+
+/// Copy the whole chat transcript of an instance
+fn copy_transcript(
+    input: &mut dyn InputBackend,
+    window_handle: i64,
+    layout: &LayoutProfile,
+) -> Result<String, String> {
+    // Select-all and copy would act on whatever window is in front
+    if !input.focus(window_handle)? {
+        return Err("Window did not come to the foreground".to_string());
+    }
+    input.sleep(200);
+    let rect = input
+        .window_rect(window_handle)
+        .ok_or("Failed to get window rect")?;
+
+    let (x, y) = layout.scroll_point.to_screen(&rect);
+    input.click(x, y)?;
+    input.sleep(80);
+    input.key_chord(&[Key::CONTROL, Key::char('a')])?;
+    input.sleep(80);
+    let copied = input.copy_selection();
+
+    // Drop the selection
+    input.key_chord(&[Key::ESCAPE])?;
+
+    copied?.ok_or_else(|| "Chat copy produced no text".to_string())
+}
+
+/// Copy the transcript and keep the last agent response on the instance
+pub fn read_response(
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<&str>,
+) -> Result<String, String> {
+    // Copying the transcript takes focus and the clipboard
+    if ctx.dry_run(project_name) {
+        return Err("Dry run: chat transcript not read".to_string());
+    }
+
+    let layout = ctx.config.get().layout_for(project_name);
+    let transcript = ctx
+        .scheduler
+        .run(window_handle, "read_response", ctx.timeout(), |token| {
+            idle::guard(ctx, window_handle)?;
+            let mut input = GuardedInput::new(input::native()?, token);
+            copy_transcript(&mut input, window_handle, &layout)
+        })?;
+
+    let auto_prompt = ctx.settings.get().auto_prompt;
+    let last_prompt = ctx.runtime.get(window_handle).and_then(|rt| rt.last_prompt);
+    let mut prompts = vec![auto_prompt.as_str()];
+    if let Some(ref prompt) = last_prompt {
+        prompts.push(prompt.as_str());
+    }
+
+    let response = extract_last_response(&transcript, &prompts);
+    ctx.runtime.with(window_handle, |rt| {
+        rt.last_response = Some(response.clone())
+    });
+    Ok(response)
+}
+
+/// Read the agent's last response from the chat
+#[tauri::command]
+pub async fn read_last_response(
+    app: tauri::AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> Result<String, String> {
+    actions::blocking(app, move |ctx| {
+        read_response(ctx, window_handle, project_name.as_deref())
+    })
+    .await?
+}
+
+/// Read the last response and check it against the configured stop conditions.
+/// A detected condition is kept as an open blocker for the next new chat.
+pub fn check_stop(
+    ctx: &ActionContext,
+    window_handle: i64,
+    project_name: Option<&str>,
+) -> Result<StopCondition, String> {
+    let settings = ctx.settings.get();
+    let response = read_response(ctx, window_handle, project_name)?;
+    let result = evaluate(&response, &settings.stop_conditions);
+
+    if result.detected {
+        let blocker = format!("{}: {}", result.condition, result.message);
+        ctx.runtime.with(window_handle, |rt| {
+            if !rt.blockers.contains(&blocker) {
+                rt.blockers.push(blocker);
+            }
+        });
+        crate::notify::log(
+            &settings,
+            "WARN",
+            &format!(
+                "[{}] Stop condition \"{}\" in last response: {}",
+                project_name.unwrap_or("window"),
+                result.condition,
+                result.message
+            ),
+        );
+    }
+    Ok(result)
+}
+
+/// Read the last response and check it against the configured stop conditions
+#[tauri::command]
+pub async fn check_stop_conditions(
+    app: tauri::AppHandle,
+    window_handle: i64,
+    project_name: Option<String>,
+) -> Result<StopCondition, String> {
+    actions::blocking(app, move |ctx| {
+        check_stop(ctx, window_handle, project_name.as_deref())
+    })
+    .await?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn response_follows_last_prompt() {
+        let transcript = "Continue\nfirst answer\nContinue\nsecond answer\n";
+        assert_eq!(
+            extract_last_response(transcript, &["Continue"]),
+            "second answer"
+        );
+        // The latest of several prompts wins
+        let transcript = "Continue\nold\nFix the tests\nnew answer";
+        assert_eq!(
+            extract_last_response(transcript, &["Continue", "  Fix the tests  "]),
+            "new answer"
+        );
+    }
+
+    #[test]
+    fn no_prompt_boundary_is_empty() {
+        let transcript = "✅ BACKLOG COMPLETADO\nolder session";
+        assert_eq!(extract_last_response(transcript, &["Continue"]), "");
+        assert_eq!(extract_last_response(transcript, &["", "  "]), "");
+        assert_eq!(extract_last_response(transcript, &[]), "");
+    }
+
+    #[test]
+    fn long_response_keeps_tail_on_char_boundary() {
+        let answer = format!("{}{}", "é".repeat(10), "ñ".repeat(MAX_RESPONSE_CHARS));
+        let transcript = format!("Continue\n{}", answer);
+        let response = extract_last_response(&transcript, &["Continue"]);
+        assert_eq!(response.chars().count(), MAX_RESPONSE_CHARS);
+        assert!(response.chars().all(|c| c == 'ñ'));
+    }
+
+    #[test]
+    fn evaluate_reports_first_condition_line() {
+        let conditions = vec![
+            String::new(),
+            "BLOQUEADO".to_string(),
+            "COMPLETADO".to_string(),
+        ];
+        let text = "working\n  ✅ BACKLOG COMPLETADO  \nBLOQUEADO: missing key";
+        let result = evaluate(text, &conditions);
+        assert!(result.detected);
+        assert_eq!(result.condition, "BLOQUEADO");
+        assert_eq!(result.message, "BLOQUEADO: missing key");
+
+        let result = evaluate("✅ BACKLOG COMPLETADO", &conditions);
+        assert_eq!(result.condition, "COMPLETADO");
+        assert_eq!(result.message, "✅ BACKLOG COMPLETADO");
+
+        assert!(!evaluate("all good", &conditions).detected);
+        assert!(!evaluate("", &conditions).detected);
+    }
+
+    #[test]
+    fn copy_needs_focus() {
+        use crate::input::{InputAction, RecordingInput};
+        use crate::layout::WindowRect;
+
+        let mut input = RecordingInput::new(WindowRect {
+            left: 0,
+            top: 0,
+            right: 800,
+            bottom: 600,
+        });
+        input.focus_ok = false;
+        let layout = LayoutProfile::right();
+        assert_eq!(
+            copy_transcript(&mut input, 7, &layout),
+            Err("Window did not come to the foreground".to_string())
+        );
+        assert_eq!(input.actions, vec![InputAction::Focus { window_handle: 7 }]);
+    }
+
+    #[test]
+    fn evaluate_truncates_message() {
+        let line = format!("STOP {}", "x".repeat(300));
+        let result = evaluate(&line, &["STOP".to_string()]);
+        assert_eq!(result.message.chars().count(), 200);
+    }
+}
@@ -0,0 +1,287 @@
+// Retry policy
+// When the agent shows a Retry button, the supervisor waits out an exponential
+// backoff (with jitter) before clicking it, and gives up once the attempts in
+// the current window reach the limit. After the cooldown the instance gets a
+// fresh budget, so a transient API outage pauses a run instead of ending it.
+
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use std::hash::{BuildHasher, Hasher};
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::lifecycle::InstanceState;
+use crate::runtime::InstanceRuntime;
+use crate::settings::Settings;
+
+/// Attempts kept per instance for notifications (attempts still inside the
+/// policy's window are kept beyond this so they all count)
+const HISTORY_LEN: usize = 20;
+
+/// Retry policy, global or per instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct RetryPolicy {
+    /// Wait before the first retry
+    pub initial_delay_ms: u64,
+    /// Each further retry waits this many times longer than the previous one
+    pub multiplier: f64,
+    pub max_delay_ms: u64,
+    /// Random spread applied to each wait, as a fraction of it (0.2 = ±20%)
+    pub jitter: f64,
+    /// Retries allowed in the window (None = the global maxRetries setting)
+    pub max_attempts: Option<u32>,
+    /// Window the attempts are counted over, in minutes (0 = since the last
+    /// successful step)
+    pub window_minutes: u32,
+    /// Minutes after giving up before the instance is re-enabled with a fresh
+    /// budget (0 = stay disabled)
+    pub cooldown_minutes: u32,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        RetryPolicy {
+            initial_delay_ms: 5000,
+            multiplier: 2.0,
+            max_delay_ms: 300_000,
+            jitter: 0.2,
+            max_attempts: None,
+            window_minutes: 0,
+            cooldown_minutes: 30,
+        }
+    }
+}
+
+impl RetryPolicy {
+    pub fn max_attempts(&self, settings: &Settings) -> u32 {
+        self.max_attempts.unwrap_or(settings.max_retries)
+    }
+
+    /// Wait before retry number `attempt` (0-based), jitter included
+    pub fn delay_ms(&self, attempt: u32) -> u64 {
+        let base = self.initial_delay_ms as f64 * self.multiplier.max(1.0).powi(attempt as i32);
+        let base = base.min(self.max_delay_ms as f64);
+        let spread = self.jitter.clamp(0.0, 1.0) * (random_unit() * 2.0 - 1.0);
+        (base * (1.0 + spread)).max(0.0) as u64
+    }
+}
+
+/// A uniformly distributed value in [0, 1) from the std hasher's random keys
+fn random_unit() -> f64 {
+    let mut hasher = std::collections::hash_map::RandomState::new().build_hasher();
+    hasher.write_u64(crate::runtime::now_ms());
+    (hasher.finish() >> 11) as f64 / (1u64 << 53) as f64
+}
+
+/// One Retry click and how long it was held back
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryAttempt {
+    pub at: u64,
+    pub delay_ms: u64,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RetryState {
+    /// Recent attempts, oldest first
+    pub attempts: VecDeque<RetryAttempt>,
+    /// When the pending retry is due (0 = none scheduled) and its wait
+    pub next_at: u64,
+    pub next_delay_ms: u64,
+    /// When an instance that gave up gets a fresh budget (0 = not cooling down)
+    pub cooldown_until: u64,
+}
+
+/// What to do about a Retry button
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Step {
+    /// Backing off; the retry is due in `remaining_ms`
+    Wait { remaining_ms: u64, attempt: u32 },
+    /// Click Retry now
+    Retry { attempt: u32 },
+    /// Out of attempts for the window
+    GiveUp { attempts: u32 },
+}
+
+/// Start of the window attempts are counted over (None = since the last
+/// successful step)
+fn window_start(policy: &RetryPolicy, now: u64) -> Option<u64> {
+    match policy.window_minutes {
+        0 => None,
+        minutes => Some(now.saturating_sub(minutes as u64 * 60 * 1000)),
+    }
+}
+
+/// Attempts that count against the budget
+fn used(policy: &RetryPolicy, rt: &InstanceRuntime, now: u64) -> u32 {
+    match window_start(policy, now) {
+        None => rt.retry_count,
+        Some(since) => rt.retry.attempts.iter().filter(|a| a.at >= since).count() as u32,
+    }
+}
+
+/// Decide on a visible Retry button, scheduling the backoff on first sight
+/// and starting the cooldown when the budget is spent
+pub fn next(policy: &RetryPolicy, settings: &Settings, rt: &mut InstanceRuntime, now: u64) -> Step {
+    let used = used(policy, rt, now);
+    if used >= policy.max_attempts(settings) {
+        rt.retry.next_at = 0;
+        rt.retry.cooldown_until = match policy.cooldown_minutes {
+            0 => 0,
+            minutes => now + minutes as u64 * 60 * 1000,
+        };
+        return Step::GiveUp { attempts: used };
+    }
+
+    if rt.retry.next_at == 0 {
+        rt.retry.next_delay_ms = policy.delay_ms(used);
+        rt.retry.next_at = now + rt.retry.next_delay_ms;
+    }
+    let attempt = used + 1;
+    if now < rt.retry.next_at {
+        Step::Wait {
+            remaining_ms: rt.retry.next_at - now,
+            attempt,
+        }
+    } else {
+        Step::Retry { attempt }
+    }
+}
+
+/// Record a Retry click
+pub fn record(policy: &RetryPolicy, rt: &mut InstanceRuntime, now: u64) {
+    rt.retry.attempts.push_back(RetryAttempt {
+        at: now,
+        delay_ms: rt.retry.next_delay_ms,
+    });
+    let since = window_start(policy, now);
+    while rt.retry.attempts.len() > HISTORY_LEN
+        && rt
+            .retry
+            .attempts
+            .front()
+            .is_some_and(|a| since.is_none_or(|since| a.at < since))
+    {
+        rt.retry.attempts.pop_front();
+    }
+    rt.retry.next_at = 0;
+    rt.retry_count += 1;
+}
+
+/// A step went through; drop any pending backoff
+pub fn succeeded(rt: &mut InstanceRuntime) {
+    rt.retry.next_at = 0;
+    rt.retry_count = 0;
+}
+
+/// Whether an instance that gave up has waited out its cooldown
+pub fn cooled_down(rt: &InstanceRuntime, now: u64) -> bool {
+    rt.retry.cooldown_until > 0
+        && now >= rt.retry.cooldown_until
+        && rt.state.state == InstanceState::Error
+}
+
+/// Forget the attempts so the instance starts with a full budget
+pub fn reset(rt: &mut InstanceRuntime) {
+    rt.retry = RetryState::default();
+    rt.retry_count = 0;
+}
+
+/// Recent attempts for a notification, e.g. "14:02:10 (+5s), 14:02:31 (+10s)"
+pub fn describe_history(rt: &InstanceRuntime) -> String {
+    if rt.retry.attempts.is_empty() {
+        return "ninguno".to_string();
+    }
+    rt.retry
+        .attempts
+        .iter()
+        .rev()
+        .take(8)
+        .rev()
+        .map(|a| {
+            let time = chrono::Local
+                .timestamp_millis_opt(a.at as i64)
+                .single()
+                .map(|t| t.format("%H:%M:%S").to_string())
+                .unwrap_or_default();
+            format!("{} (+{}s)", time, a.delay_ms / 1000)
+        })
+        .collect::<Vec<_>>()
+        .join(", ")
+}
+
+/// Get the retry policy for an instance (or the global one)
+#[tauri::command]
+pub fn get_retry_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> RetryPolicy {
+    config.get().retry_for(project_name.as_deref())
+}
+
+/// Set the global retry policy, or an instance override when project_name is given
+#[tauri::command]
+pub fn set_retry_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    policy: RetryPolicy,
+) -> Result<(), String> {
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .retry = Some(policy);
+        }
+        None => c.retry = policy,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn policy(max_attempts: u32, window_minutes: u32) -> RetryPolicy {
+        RetryPolicy {
+            jitter: 0.0,
+            max_attempts: Some(max_attempts),
+            window_minutes,
+            ..RetryPolicy::default()
+        }
+    }
+
+    /// Click Retry whenever it's due until the policy gives up
+    fn attempts_until_give_up(policy: &RetryPolicy) -> u32 {
+        let settings = Settings::default();
+        let mut rt = InstanceRuntime::default();
+        let mut now = 1_000_000;
+        loop {
+            match next(policy, &settings, &mut rt, now) {
+                Step::Wait { remaining_ms, .. } => now += remaining_ms,
+                Step::Retry { .. } => record(policy, &mut rt, now),
+                Step::GiveUp { attempts } => return attempts,
+            }
+        }
+    }
+
+    #[test]
+    fn gives_up_beyond_history_len() {
+        let over = HISTORY_LEN as u32 + 10;
+        assert_eq!(attempts_until_give_up(&policy(over, 24 * 60)), over);
+        assert_eq!(attempts_until_give_up(&policy(over, 0)), over);
+        assert_eq!(attempts_until_give_up(&policy(3, 60)), 3);
+    }
+
+    #[test]
+    fn history_drops_attempts_outside_the_window() {
+        let policy = policy(100, 1);
+        let mut rt = InstanceRuntime::default();
+        for i in 0..HISTORY_LEN as u64 + 5 {
+            record(&policy, &mut rt, i * 60 * 1000);
+        }
+        assert_eq!(rt.retry.attempts.len(), HISTORY_LEN);
+        assert_eq!(rt.retry_count, HISTORY_LEN as u32 + 5);
+    }
+}
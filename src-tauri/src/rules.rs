@@ -0,0 +1,570 @@
+// Stop-condition rules
+// A rule matches the agent's last response (substring or regex) or the
+// backlog state, applies globally or to one project, and carries the actions
+// to take when it fires. The legacy `stopConditions` setting still works: each
+// entry becomes a global substring rule that blocks the instance and notifies.
+
+use regex::RegexBuilder;
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+
+use crate::config::{BobConfig, ConfigStore};
+use crate::notify;
+use crate::runtime::{now_ms, Runtime};
+use crate::settings::Settings;
+use crate::BacklogResult;
+
+/// Firings kept per instance
+const FIRINGS_LEN: usize = 50;
+
+/// Stop condition the agent reports once the whole backlog is done
+pub const BACKLOG_DONE: &str = "✅ BACKLOG COMPLETADO";
+
+/// Which instances a rule applies to
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleScope {
+    #[default]
+    Global,
+    #[serde(rename_all = "camelCase")]
+    Instance { project_name: String },
+}
+
+/// Backlog states a rule can react to
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum BacklogPredicate {
+    /// Every issue is checked off
+    AllCompleted,
+    /// At least this many issues are completed
+    CompletedAtLeast { count: i32 },
+    /// The issue in progress mentions the text (case-insensitive)
+    CurrentIssueContains { text: String },
+}
+
+/// What a rule looks at
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleMatcher {
+    /// Text contained in the last response
+    #[serde(rename_all = "camelCase")]
+    Substring {
+        text: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    /// Regular expression matched against the last response
+    #[serde(rename_all = "camelCase")]
+    Regex {
+        pattern: String,
+        #[serde(default)]
+        case_sensitive: bool,
+    },
+    Backlog {
+        predicate: BacklogPredicate,
+    },
+}
+
+/// What happens when a rule fires; a rule can carry several
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum RuleAction {
+    /// Take the instance out of the loop until it's re-enabled
+    Pause,
+    /// Mark the instance blocked (default reason: rule name and matched text)
+    Block {
+        #[serde(default)]
+        reason: Option<String>,
+    },
+    /// Mark the instance complete
+    Complete,
+    /// Send a Discord notification (defaults describe the rule and match)
+    Notify {
+        #[serde(default)]
+        title: Option<String>,
+        #[serde(default)]
+        message: Option<String>,
+    },
+    /// Answer the agent with this text instead of the auto prompt
+    Reply { text: String },
+    /// Run a shell command; BOB_PROJECT, BOB_RULE and BOB_MATCH are set
+    Hook { command: String },
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rule {
+    pub name: String,
+    #[serde(default = "default_true")]
+    pub enabled: bool,
+    #[serde(default)]
+    pub scope: RuleScope,
+    pub matcher: RuleMatcher,
+    pub actions: Vec<RuleAction>,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Rule {
+    pub fn applies_to(&self, project_name: &str) -> bool {
+        self.enabled
+            && match self.scope {
+                RuleScope::Global => true,
+                RuleScope::Instance {
+                    project_name: ref p,
+                } => p == project_name,
+            }
+    }
+
+    pub fn is_backlog(&self) -> bool {
+        matches!(self.matcher, RuleMatcher::Backlog { .. })
+    }
+
+    /// Check that the rule can be evaluated (named, has actions, regex compiles)
+    pub fn validate(&self) -> Result<(), String> {
+        if self.name.trim().is_empty() {
+            return Err("Rule name cannot be empty".to_string());
+        }
+        if self.actions.is_empty() {
+            return Err(format!("Rule \"{}\" has no actions", self.name));
+        }
+        if let RuleMatcher::Regex {
+            ref pattern,
+            case_sensitive,
+        } = self.matcher
+        {
+            RegexBuilder::new(pattern)
+                .case_insensitive(!case_sensitive)
+                .build()
+                .map_err(|e| format!("Rule \"{}\": invalid regex: {}", self.name, e))?;
+        }
+        Ok(())
+    }
+
+    /// The response line the rule matches, if any
+    pub fn match_text(&self, text: &str) -> Option<String> {
+        let line = match self.matcher {
+            RuleMatcher::Substring {
+                text: ref needle,
+                case_sensitive,
+            } if !needle.is_empty() => {
+                if case_sensitive {
+                    text.lines().find(|l| l.contains(needle.as_str()))
+                } else {
+                    let needle = needle.to_lowercase();
+                    text.lines().find(|l| l.to_lowercase().contains(&needle))
+                }
+            }
+            RuleMatcher::Regex {
+                ref pattern,
+                case_sensitive,
+            } => {
+                let regex = RegexBuilder::new(pattern)
+                    .case_insensitive(!case_sensitive)
+                    .build()
+                    .ok()?;
+                text.lines().find(|l| regex.is_match(l))
+            }
+            _ => None,
+        }?;
+        Some(line.trim().chars().take(200).collect())
+    }
+
+    /// A description of the backlog state the rule matches, if any
+    pub fn match_backlog(&self, backlog: &BacklogResult) -> Option<String> {
+        let RuleMatcher::Backlog { ref predicate } = self.matcher else {
+            return None;
+        };
+        let progress = format!(
+            "{}/{} issues completed",
+            backlog.completed_issues, backlog.total_issues
+        );
+        match predicate {
+            BacklogPredicate::AllCompleted => (backlog.total_issues > 0
+                && backlog.completed_issues >= backlog.total_issues)
+                .then_some(progress),
+            BacklogPredicate::CompletedAtLeast { count } => {
+                (backlog.completed_issues >= *count).then_some(progress)
+            }
+            BacklogPredicate::CurrentIssueContains { text } => (!text.is_empty()
+                && backlog
+                    .current_issue
+                    .to_lowercase()
+                    .contains(&text.to_lowercase()))
+            .then(|| backlog.current_issue.clone()),
+        }
+    }
+}
+
+/// Rules for an instance: configured rules in scope, then the legacy stop conditions
+pub fn rules_for(config: &BobConfig, settings: &Settings, project_name: &str) -> Vec<Rule> {
+    let mut rules: Vec<Rule> = config
+        .rules
+        .iter()
+        .filter(|r| r.applies_to(project_name))
+        .cloned()
+        .collect();
+    rules.extend(legacy_rules(settings));
+    rules
+}
+
+/// The flat stop-condition list as rules, with the effect it always had
+fn legacy_rules(settings: &Settings) -> Vec<Rule> {
+    settings
+        .stop_conditions
+        .iter()
+        .filter(|c| !c.is_empty())
+        .map(|condition| {
+            let complete = condition == BACKLOG_DONE;
+            let mut actions = vec![if complete {
+                RuleAction::Complete
+            } else {
+                RuleAction::Block { reason: None }
+            }];
+            let notify = if complete {
+                settings.notify_on_complete
+            } else {
+                settings.notify_on_error
+            };
+            if notify {
+                actions.push(RuleAction::Notify {
+                    title: None,
+                    message: None,
+                });
+            }
+            Rule {
+                name: condition.clone(),
+                enabled: true,
+                scope: RuleScope::Global,
+                matcher: RuleMatcher::Substring {
+                    text: condition.clone(),
+                    case_sensitive: true,
+                },
+                actions,
+            }
+        })
+        .collect()
+}
+
+/// A rule that fired on an instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RuleFiring {
+    pub rule: String,
+    /// Response line or backlog state that matched
+    pub matched: String,
+    pub actions: Vec<RuleAction>,
+    pub at: u64,
+    /// What the match was against (prompt time for responses, completed
+    /// count for the backlog); the same rule doesn't fire twice on one context
+    #[serde(skip)]
+    pub context: u64,
+}
+
+/// Record a firing unless the rule already fired on the same match and context
+pub fn record(
+    runtime: &Runtime,
+    window_handle: i64,
+    rule: &Rule,
+    matched: String,
+    context: u64,
+) -> Option<RuleFiring> {
+    runtime.with(window_handle, |rt| {
+        let repeated = rt
+            .rule_firings
+            .iter()
+            .any(|f| f.rule == rule.name && f.matched == matched && f.context == context);
+        if repeated {
+            return None;
+        }
+
+        let firing = RuleFiring {
+            rule: rule.name.clone(),
+            matched,
+            actions: rule.actions.clone(),
+            at: now_ms(),
+            context,
+        };
+        if rt.rule_firings.len() == FIRINGS_LEN {
+            rt.rule_firings.pop_front();
+        }
+        rt.rule_firings.push_back(firing.clone());
+        Some(firing)
+    })
+}
+
+/// Run a rule's hook command without waiting for it (logged only in dry-run)
+pub fn run_hook(
+    settings: &Settings,
+    dry_run: bool,
+    command: &str,
+    project_name: &str,
+    firing: &RuleFiring,
+) {
+    let line = format!(
+        "[{}] Rule \"{}\" hook: {}",
+        project_name, firing.rule, command
+    );
+    if dry_run {
+        notify::log(settings, "DRYRUN", &line);
+        return;
+    }
+    notify::log(settings, "INFO", &line);
+
+    let mut process = if cfg!(target_os = "windows") {
+        let mut c = std::process::Command::new("cmd");
+        c.args(["/C", command]);
+        c
+    } else {
+        let mut c = std::process::Command::new("sh");
+        c.args(["-c", command]);
+        c
+    };
+    let spawned = process
+        .env("BOB_PROJECT", project_name)
+        .env("BOB_RULE", &firing.rule)
+        .env("BOB_MATCH", &firing.matched)
+        .spawn();
+    if let Err(e) = spawned {
+        notify::log(
+            settings,
+            "ERROR",
+            &format!("[{}] Hook failed to start: {}", project_name, e),
+        );
+    }
+}
+
+/// Configured rules (the legacy stop conditions are not included)
+#[tauri::command]
+pub fn get_rules(config: tauri::State<'_, ConfigStore>) -> Vec<Rule> {
+    config.get().rules
+}
+
+/// Replace the configured rules
+#[tauri::command]
+pub fn save_rules(config: tauri::State<'_, ConfigStore>, rules: Vec<Rule>) -> Result<(), String> {
+    for rule in &rules {
+        rule.validate()?;
+    }
+    config.update(|c| c.rules = rules)
+}
+
+/// Rules that fired on an instance, oldest first
+#[tauri::command]
+pub fn get_rule_firings(
+    runtime: tauri::State<'_, Runtime>,
+    window_handle: i64,
+) -> VecDeque<RuleFiring> {
+    runtime
+        .get(window_handle)
+        .map(|rt| rt.rule_firings)
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn rule(name: &str, matcher: RuleMatcher) -> Rule {
+        Rule {
+            name: name.to_string(),
+            enabled: true,
+            scope: RuleScope::Global,
+            matcher,
+            actions: vec![RuleAction::Pause],
+        }
+    }
+
+    fn substring(text: &str, case_sensitive: bool) -> Rule {
+        rule(
+            text,
+            RuleMatcher::Substring {
+                text: text.to_string(),
+                case_sensitive,
+            },
+        )
+    }
+
+    fn regex(pattern: &str, case_sensitive: bool) -> Rule {
+        rule(
+            pattern,
+            RuleMatcher::Regex {
+                pattern: pattern.to_string(),
+                case_sensitive,
+            },
+        )
+    }
+
+    fn backlog(predicate: BacklogPredicate) -> Rule {
+        rule("backlog", RuleMatcher::Backlog { predicate })
+    }
+
+    const RESPONSE: &str = "Ran the tests\n  Error: Rate limit reached  \nWaiting";
+
+    #[test]
+    fn substring_returns_matching_line() {
+        assert_eq!(
+            substring("rate limit", false).match_text(RESPONSE),
+            Some("Error: Rate limit reached".to_string())
+        );
+        assert_eq!(substring("rate limit", true).match_text(RESPONSE), None);
+        assert_eq!(
+            substring("Rate limit", true).match_text(RESPONSE),
+            Some("Error: Rate limit reached".to_string())
+        );
+        assert_eq!(substring("", false).match_text(RESPONSE), None);
+    }
+
+    #[test]
+    fn regex_matches_lines() {
+        assert_eq!(
+            regex(r"^error: \w+", false).match_text(RESPONSE),
+            None,
+            "lines are matched untrimmed"
+        );
+        assert_eq!(
+            regex(r"error: rate\s+limit", false).match_text(RESPONSE),
+            Some("Error: Rate limit reached".to_string())
+        );
+        assert_eq!(regex(r"error: rate", true).match_text(RESPONSE), None);
+        // An invalid pattern never matches (and is rejected on save)
+        assert_eq!(regex(r"(unclosed", false).match_text(RESPONSE), None);
+        assert!(regex(r"(unclosed", false).validate().is_err());
+    }
+
+    #[test]
+    fn backlog_predicates() {
+        let state = BacklogResult {
+            total_issues: 5,
+            completed_issues: 3,
+            current_issue: "Add OAuth login".to_string(),
+            ..BacklogResult::default()
+        };
+        let done = BacklogResult {
+            completed_issues: 5,
+            ..state.clone()
+        };
+
+        let all = backlog(BacklogPredicate::AllCompleted);
+        assert_eq!(all.match_backlog(&state), None);
+        assert_eq!(
+            all.match_backlog(&done),
+            Some("5/5 issues completed".to_string())
+        );
+        assert_eq!(all.match_backlog(&BacklogResult::default()), None);
+
+        let three = backlog(BacklogPredicate::CompletedAtLeast { count: 3 });
+        assert_eq!(
+            three.match_backlog(&state),
+            Some("3/5 issues completed".to_string())
+        );
+        let four = backlog(BacklogPredicate::CompletedAtLeast { count: 4 });
+        assert_eq!(four.match_backlog(&state), None);
+
+        let oauth = backlog(BacklogPredicate::CurrentIssueContains {
+            text: "oauth".to_string(),
+        });
+        assert_eq!(
+            oauth.match_backlog(&state),
+            Some("Add OAuth login".to_string())
+        );
+        let empty = backlog(BacklogPredicate::CurrentIssueContains {
+            text: String::new(),
+        });
+        assert_eq!(empty.match_backlog(&state), None);
+
+        // Text and backlog matchers don't cross over
+        assert_eq!(all.match_text(RESPONSE), None);
+        assert_eq!(substring("Add", false).match_backlog(&state), None);
+    }
+
+    #[test]
+    fn scope_filters_rules() {
+        let mut own = substring("own", false);
+        own.scope = RuleScope::Instance {
+            project_name: "bob".to_string(),
+        };
+        let mut off = substring("off", false);
+        off.enabled = false;
+        let config = BobConfig {
+            rules: vec![substring("global", false), own, off],
+            ..BobConfig::default()
+        };
+        let settings = Settings {
+            stop_conditions: Vec::new(),
+            ..Settings::default()
+        };
+
+        let names = |project: &str| {
+            rules_for(&config, &settings, project)
+                .into_iter()
+                .map(|r| r.name)
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(names("bob"), vec!["global", "own"]);
+        assert_eq!(names("other"), vec!["global"]);
+    }
+
+    #[test]
+    fn legacy_stop_conditions_become_rules() {
+        let settings = Settings {
+            stop_conditions: vec![
+                BACKLOG_DONE.to_string(),
+                String::new(),
+                "BLOQUEADO".to_string(),
+            ],
+            notify_on_complete: true,
+            notify_on_error: false,
+            ..Settings::default()
+        };
+        let rules = legacy_rules(&settings);
+        assert_eq!(rules.len(), 2);
+
+        assert_eq!(rules[0].name, BACKLOG_DONE);
+        assert_eq!(rules[0].scope, RuleScope::Global);
+        assert_eq!(
+            rules[0].actions,
+            vec![
+                RuleAction::Complete,
+                RuleAction::Notify {
+                    title: None,
+                    message: None
+                }
+            ]
+        );
+
+        assert_eq!(
+            rules[1].matcher,
+            RuleMatcher::Substring {
+                text: "BLOQUEADO".to_string(),
+                case_sensitive: true
+            }
+        );
+        assert_eq!(rules[1].actions, vec![RuleAction::Block { reason: None }]);
+    }
+
+    #[test]
+    fn record_fires_once_per_match_and_context() {
+        let runtime = Runtime::default();
+        let rule = substring("limit", false);
+        let fire =
+            |matched: &str, context| record(&runtime, 1, &rule, matched.to_string(), context);
+
+        assert!(fire("rate limit", 100).is_some());
+        assert!(fire("rate limit", 100).is_none());
+        // A new prompt or a different line fires again
+        assert!(fire("rate limit", 200).is_some());
+        assert!(fire("hard limit", 200).is_some());
+
+        let other = substring("rate", false);
+        assert!(record(&runtime, 1, &other, "rate limit".to_string(), 100).is_some());
+        // Instances are tracked separately
+        assert!(record(&runtime, 2, &rule, "rate limit".to_string(), 100).is_some());
+
+        let firings = runtime.get(1).unwrap().rule_firings;
+        assert_eq!(firings.len(), 4);
+        assert_eq!(firings[0].actions, vec![RuleAction::Pause]);
+    }
+}
@@ -0,0 +1,85 @@
+// Per-instance backend state, keyed by window handle
+// Lives for the whole session; commands and monitors read and update it
+
+use serde::Serialize;
+use std::collections::{HashMap, VecDeque};
+use std::sync::Mutex;
+
+use crate::budget::BudgetUsage;
+use crate::lifecycle::StateMachine;
+use crate::playbook::PlaybookProgress;
+use crate::retry::RetryState;
+use crate::rules::RuleFiring;
+use crate::stall::VisualHistory;
+use crate::{BacklogResult, UIStateResult};
+
+/// Milliseconds since the Unix epoch (same unit as the frontend's Date.now())
+pub fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InstanceRuntime {
+    pub project_name: Option<String>,
+    /// Lifecycle state and how it got there
+    pub state: StateMachine,
+    pub visual: VisualHistory,
+    /// Last prompt written to the chat and when
+    pub last_prompt: Option<String>,
+    pub last_prompt_at: u64,
+    /// Last agent response read from the chat
+    pub last_response: Option<String>,
+    /// A focus-taking action was postponed because the user is active
+    pub waiting_for_idle: bool,
+    /// Latest successful backlog read
+    pub backlog: Option<BacklogResult>,
+    /// Prompts sent since the chat was last reset
+    pub prompts_since_new_chat: u32,
+    /// Open problems (stop conditions seen) to carry into a new chat
+    pub blockers: Vec<String>,
+    /// Prompts sent by the supervisor since auto-implementation started
+    pub step_count: u32,
+    /// Consecutive Retry clicks since the last successful step
+    pub retry_count: u32,
+    /// Retry backoff, attempt history and cooldown
+    pub retry: RetryState,
+    /// Last time the supervisor acted on the instance
+    pub last_activity: u64,
+    /// When the inactivity recovery prompt was sent (0 = not since the last regular prompt)
+    pub recovery_prompt_at: u64,
+    /// When a schedule-closed instance next runs (0 = open, or no upcoming window)
+    pub next_run_at: u64,
+    /// Latest UI detection and when it ran
+    pub last_detection: Option<UIStateResult>,
+    pub last_detection_at: u64,
+    /// Rules that fired on the instance, oldest first
+    pub rule_firings: VecDeque<RuleFiring>,
+    /// Canned reply from a rule, sent instead of the next auto prompt
+    pub pending_reply: Option<String>,
+    /// Current playbook step, when a playbook drives the instance
+    pub playbook: Option<PlaybookProgress>,
+    /// Prompts and time counted against the budgets
+    pub budget: BudgetUsage,
+}
+
+#[derive(Default)]
+pub struct Runtime {
+    instances: Mutex<HashMap<i64, InstanceRuntime>>,
+}
+
+impl Runtime {
+    /// Mutate an instance's state, creating it on first use
+    pub fn with<R>(&self, window_handle: i64, f: impl FnOnce(&mut InstanceRuntime) -> R) -> R {
+        let mut instances = self.instances.lock().unwrap();
+        f(instances.entry(window_handle).or_default())
+    }
+
+    /// Snapshot of an instance's state
+    pub fn get(&self, window_handle: i64) -> Option<InstanceRuntime> {
+        self.instances.lock().unwrap().get(&window_handle).cloned()
+    }
+}
@@ -0,0 +1,432 @@
+// Run schedules and quiet hours
+// An instance runs only during minutes that match one of its cron run windows
+// (none = always) and outside its quiet hours. The global schedule and the
+// instance's own schedule both apply. Outside its window the supervisor leaves
+// the instance alone and resumes it when the next window opens.
+
+use chrono::{DateTime, Datelike, Duration, Local, NaiveTime, Timelike, Weekday};
+use serde::{Deserialize, Serialize};
+use std::str::FromStr;
+
+use crate::config::{ConfigStore, InstanceConfig};
+
+/// Window changes looked at when searching for the next run (a week of
+/// alternating run windows and quiet hours is well below this)
+const MAX_STEPS: usize = 1000;
+
+/// Schedule, global or per instance
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Schedule {
+    /// Cron expressions (`min hour day month weekday`, or with a leading
+    /// seconds field) for the minutes the instance may run; empty = always
+    pub run_windows: Vec<String>,
+    /// Windows it must not run in: `HH:MM-HH:MM`, optionally preceded by days
+    /// (`Mon-Fri 09:00-10:30`, `Sat,Sun 00:00-12:00`); may cross midnight
+    pub quiet_hours: Vec<String>,
+}
+
+impl Schedule {
+    pub fn is_empty(&self) -> bool {
+        self.run_windows.iter().all(|w| w.trim().is_empty())
+            && self.quiet_hours.iter().all(|q| q.trim().is_empty())
+    }
+
+    /// Check that every window and quiet-hours entry parses
+    pub fn validate(&self) -> Result<(), String> {
+        self.compile().map(|_| ())
+    }
+
+    fn compile(&self) -> Result<Compiled, String> {
+        let run_windows = self
+            .run_windows
+            .iter()
+            .filter(|w| !w.trim().is_empty())
+            .map(|w| parse_cron(w))
+            .collect::<Result<_, _>>()?;
+        let quiet_hours = self
+            .quiet_hours
+            .iter()
+            .filter(|q| !q.trim().is_empty())
+            .map(|q| QuietHours::parse(q))
+            .collect::<Result<_, _>>()?;
+        Ok(Compiled {
+            run_windows,
+            quiet_hours,
+        })
+    }
+}
+
+/// Five-field expressions get a zero seconds field, which the cron crate requires
+fn parse_cron(expression: &str) -> Result<cron::Schedule, String> {
+    let expression = expression.trim();
+    let invalid = |why: String| format!("Invalid run window \"{}\": {}", expression, why);
+    let mut fields = expression
+        .split_whitespace()
+        .map(str::to_string)
+        .collect::<Vec<_>>();
+    if fields.len() == 5 {
+        fields.insert(0, "0".to_string());
+    }
+    // Weekday is the sixth field, before the optional year
+    if let Some(days) = fields.get_mut(5) {
+        *days = crontab_weekdays(days).map_err(invalid)?;
+    }
+    cron::Schedule::from_str(&fields.join(" ")).map_err(|e| invalid(e.to_string()))
+}
+
+/// Crontab numbers weekdays 0-7 with Sunday as 0 or 7, the cron crate 1-7
+/// from Sunday. Numeric days are rewritten as names so they mean what they
+/// would in a crontab; names, `*` and `?` are passed through.
+fn crontab_weekdays(field: &str) -> Result<String, String> {
+    const NAMES: [&str; 7] = ["SUN", "MON", "TUE", "WED", "THU", "FRI", "SAT"];
+    let number = |text: &str| text.parse::<usize>().ok().filter(|n| *n <= 7);
+
+    let mut days = Vec::new();
+    for item in field.split(',') {
+        let (base, step) = match item.split_once('/') {
+            Some((base, step)) => (base, Some(step)),
+            None => (item, None),
+        };
+        let range = match base.split_once('-') {
+            Some((from, to)) => number(from).zip(number(to)),
+            // "n/step" runs from n to the end of the week
+            None => number(base).map(|n| (n, if step.is_some() { 7 } else { n })),
+        };
+        let Some((from, to)) = range else {
+            days.push(item.to_string());
+            continue;
+        };
+        if from > to {
+            return Err(format!("invalid day range \"{}\"", base));
+        }
+        let step = match step {
+            Some(step) => step
+                .parse::<usize>()
+                .ok()
+                .filter(|s| *s > 0)
+                .ok_or_else(|| format!("invalid step \"{}\"", step))?,
+            None => 1,
+        };
+        days.extend((from..=to).step_by(step).map(|d| NAMES[d % 7].to_string()));
+    }
+    Ok(days.join(","))
+}
+
+/// A parsed quiet-hours entry
+#[derive(Debug, Clone)]
+struct QuietHours {
+    /// Days the window starts on (empty = every day)
+    days: Vec<Weekday>,
+    start: NaiveTime,
+    end: NaiveTime,
+}
+
+impl QuietHours {
+    fn parse(text: &str) -> Result<Self, String> {
+        let invalid = |why: &str| format!("Invalid quiet hours \"{}\": {}", text.trim(), why);
+        let mut parts = text.split_whitespace().collect::<Vec<_>>();
+        let range = parts.pop().ok_or_else(|| invalid("empty"))?;
+        let days = match parts.as_slice() {
+            [] => Vec::new(),
+            [days] => parse_days(days).map_err(|e| invalid(&e))?,
+            _ => return Err(invalid("expected [days] HH:MM-HH:MM")),
+        };
+        let (start, end) = range
+            .split_once('-')
+            .ok_or_else(|| invalid("expected HH:MM-HH:MM"))?;
+        let time = |t: &str| {
+            NaiveTime::parse_from_str(t, "%H:%M").map_err(|_| invalid("times must be HH:MM"))
+        };
+        Ok(QuietHours {
+            days,
+            start: time(start)?,
+            end: time(end)?,
+        })
+    }
+
+    fn on(&self, day: Weekday) -> bool {
+        self.days.is_empty() || self.days.contains(&day)
+    }
+
+    /// End of the quiet window `at` falls in, if any
+    fn end_if_within(&self, at: DateTime<Local>) -> Option<DateTime<Local>> {
+        let time = at.time();
+        let today = at.weekday();
+        let at_time = |date: DateTime<Local>, t: NaiveTime| {
+            date.with_hour(t.hour())
+                .and_then(|d| d.with_minute(t.minute()))
+                .and_then(|d| d.with_second(0))
+                .and_then(|d| d.with_nanosecond(0))
+        };
+
+        if self.start < self.end {
+            (self.on(today) && time >= self.start && time < self.end)
+                .then(|| at_time(at, self.end))
+                .flatten()
+        } else if time >= self.start && self.on(today) {
+            // Crosses midnight: started today, ends tomorrow
+            at_time(at + Duration::days(1), self.end)
+        } else if time < self.end && self.on(today.pred()) {
+            // Crosses midnight: started yesterday, ends today
+            at_time(at, self.end)
+        } else {
+            None
+        }
+    }
+}
+
+/// Comma-separated days or ranges: `Mon-Fri`, `Sat,Sun`, `Mon,Wed-Fri`
+fn parse_days(text: &str) -> Result<Vec<Weekday>, String> {
+    let mut days = Vec::new();
+    for part in text.split(',') {
+        let day = |d: &str| {
+            Weekday::from_str(d.trim()).map_err(|_| format!("unknown day \"{}\"", d.trim()))
+        };
+        match part.split_once('-') {
+            Some((from, to)) => {
+                let (mut d, to) = (day(from)?, day(to)?);
+                days.push(d);
+                while d != to {
+                    d = d.succ();
+                    days.push(d);
+                }
+            }
+            None => days.push(day(part)?),
+        }
+    }
+    Ok(days)
+}
+
+struct Compiled {
+    run_windows: Vec<cron::Schedule>,
+    quiet_hours: Vec<QuietHours>,
+}
+
+impl Compiled {
+    /// When `at` can't run, the earliest time it might: the end of the quiet
+    /// hours it falls in, or the next run window
+    fn blocked_until(&self, at: DateTime<Local>) -> Option<Option<DateTime<Local>>> {
+        if let Some(end) = self
+            .quiet_hours
+            .iter()
+            .filter_map(|q| q.end_if_within(at))
+            .max()
+        {
+            return Some(Some(end));
+        }
+        if self.run_windows.is_empty() {
+            return None;
+        }
+        let minute = at.with_second(0).and_then(|t| t.with_nanosecond(0))?;
+        if self.run_windows.iter().any(|w| w.includes(minute)) {
+            return None;
+        }
+        Some(
+            self.run_windows
+                .iter()
+                .filter_map(|w| w.after(&at).next())
+                .min(),
+        )
+    }
+}
+
+/// Earliest time from `at` on that every schedule allows (None = never, e.g.
+/// a run window that can't match)
+pub fn next_run(schedules: &[Schedule], at: DateTime<Local>) -> Result<Option<u64>, String> {
+    let compiled = schedules
+        .iter()
+        .map(Schedule::compile)
+        .collect::<Result<Vec<_>, _>>()?;
+
+    let mut t = at;
+    for _ in 0..MAX_STEPS {
+        match compiled.iter().find_map(|c| c.blocked_until(t)) {
+            None => return Ok(Some(t.timestamp_millis() as u64)),
+            Some(Some(next)) => t = next,
+            Some(None) => return Ok(None),
+        }
+    }
+    Ok(None)
+}
+
+/// Where an instance stands against its schedules
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Window {
+    Open,
+    /// Closed until `next_run` (0 = no upcoming window)
+    Closed {
+        next_run: u64,
+    },
+}
+
+/// Evaluate an instance's schedules at `now`
+pub fn window(schedules: &[Schedule], now: DateTime<Local>) -> Result<Window, String> {
+    let now_ms = now.timestamp_millis() as u64;
+    Ok(match next_run(schedules, now)? {
+        Some(at) if at <= now_ms => Window::Open,
+        Some(at) => Window::Closed { next_run: at },
+        None => Window::Closed { next_run: 0 },
+    })
+}
+
+/// Get the global schedule, or an instance's own when project_name is given
+#[tauri::command]
+pub fn get_schedule(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> Schedule {
+    let config = config.get();
+    match project_name {
+        Some(name) => config
+            .instances
+            .get(&name)
+            .and_then(|i| i.schedule.clone())
+            .unwrap_or_default(),
+        None => config.schedule,
+    }
+}
+
+/// Set the global schedule, or an instance's own when project_name is given
+#[tauri::command]
+pub fn set_schedule(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    schedule: Schedule,
+) -> Result<(), String> {
+    schedule.validate()?;
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .schedule = Some(schedule).filter(|s| !s.is_empty());
+        }
+        None => c.schedule = schedule,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    /// 2024-01-01 is a Monday
+    fn at(day: u32, hour: u32, minute: u32) -> DateTime<Local> {
+        Local
+            .with_ymd_and_hms(2024, 1, day, hour, minute, 0)
+            .unwrap()
+    }
+
+    fn ms(t: DateTime<Local>) -> u64 {
+        t.timestamp_millis() as u64
+    }
+
+    fn runs(windows: &[&str]) -> Schedule {
+        Schedule {
+            run_windows: windows.iter().map(|w| w.to_string()).collect(),
+            quiet_hours: Vec::new(),
+        }
+    }
+
+    fn quiet(hours: &[&str]) -> Schedule {
+        Schedule {
+            run_windows: Vec::new(),
+            quiet_hours: hours.iter().map(|q| q.to_string()).collect(),
+        }
+    }
+
+    #[test]
+    fn no_schedule_is_always_open() {
+        assert_eq!(window(&[], at(1, 3, 0)), Ok(Window::Open));
+        assert_eq!(
+            window(&[Schedule::default()], at(6, 23, 59)),
+            Ok(Window::Open)
+        );
+    }
+
+    #[test]
+    fn weekdays_use_crontab_numbering() {
+        let weekdays = [runs(&["* * * * 1-5"])];
+        assert_eq!(window(&weekdays, at(1, 10, 0)), Ok(Window::Open));
+        assert_eq!(window(&weekdays, at(5, 23, 59)), Ok(Window::Open));
+        assert_eq!(
+            window(&weekdays, at(6, 12, 0)),
+            Ok(Window::Closed {
+                next_run: ms(at(8, 0, 0))
+            })
+        );
+
+        for sunday in ["0", "7", "SUN"] {
+            let schedule = [runs(&[&format!("* * * * {}", sunday)])];
+            assert_eq!(window(&schedule, at(7, 12, 0)), Ok(Window::Open));
+            assert_eq!(next_run(&schedule, at(6, 12, 0)), Ok(Some(ms(at(7, 0, 0)))));
+        }
+
+        // Fri-Sun through the end of the week
+        let weekend = [runs(&["* * * * 5-7"])];
+        assert_eq!(window(&weekend, at(7, 12, 0)), Ok(Window::Open));
+        assert_eq!(next_run(&weekend, at(1, 12, 0)), Ok(Some(ms(at(5, 0, 0)))));
+    }
+
+    #[test]
+    fn rejects_invalid_weekdays() {
+        assert!(runs(&["* * * * 8"]).validate().is_err());
+        assert!(runs(&["* * * * 5-1"]).validate().is_err());
+        assert!(runs(&["* * * * 1-5/0"]).validate().is_err());
+    }
+
+    #[test]
+    fn quiet_hours_cross_midnight() {
+        let night = [quiet(&["22:00-06:00"])];
+        assert_eq!(window(&night, at(1, 12, 0)), Ok(Window::Open));
+        assert_eq!(
+            window(&night, at(1, 23, 30)),
+            Ok(Window::Closed {
+                next_run: ms(at(2, 6, 0))
+            })
+        );
+        assert_eq!(
+            window(&night, at(2, 3, 0)),
+            Ok(Window::Closed {
+                next_run: ms(at(2, 6, 0))
+            })
+        );
+        assert_eq!(window(&night, at(2, 6, 0)), Ok(Window::Open));
+    }
+
+    #[test]
+    fn quiet_days_name_the_starting_day() {
+        let friday_night = [quiet(&["Fri 22:00-06:00"])];
+        // Thursday night isn't quiet, Friday night runs into Saturday
+        assert_eq!(window(&friday_night, at(5, 3, 0)), Ok(Window::Open));
+        assert_eq!(
+            next_run(&friday_night, at(5, 22, 0)),
+            Ok(Some(ms(at(6, 6, 0))))
+        );
+        assert_eq!(
+            next_run(&friday_night, at(6, 3, 0)),
+            Ok(Some(ms(at(6, 6, 0))))
+        );
+        assert_eq!(window(&friday_night, at(6, 22, 30)), Ok(Window::Open));
+    }
+
+    #[test]
+    fn next_run_combines_windows_and_quiet_hours() {
+        let office = [Schedule {
+            run_windows: vec!["* 9-17 * * 1-5".to_string()],
+            quiet_hours: vec!["12:00-13:00".to_string()],
+        }];
+        assert_eq!(next_run(&office, at(1, 10, 0)), Ok(Some(ms(at(1, 10, 0)))));
+        assert_eq!(next_run(&office, at(1, 12, 30)), Ok(Some(ms(at(1, 13, 0)))));
+        assert_eq!(next_run(&office, at(1, 18, 0)), Ok(Some(ms(at(2, 9, 0)))));
+        assert_eq!(next_run(&office, at(5, 18, 0)), Ok(Some(ms(at(8, 9, 0)))));
+
+        // The global and the instance schedule both apply
+        let late = quiet(&["09:00-11:00"]);
+        assert_eq!(
+            next_run(&[office[0].clone(), late], at(1, 8, 0)),
+            Ok(Some(ms(at(1, 11, 0))))
+        );
+    }
+}
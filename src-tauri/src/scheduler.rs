@@ -0,0 +1,506 @@
+// Global action queue
+// Every focus-taking operation (detection, clicks, pastes, chat reads) runs
+// through one queue so two instances never fight over the foreground window.
+// Instances take turns round-robin; each action has a timeout and can be cancelled.
+
+use serde::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex};
+use std::time::{Duration, Instant};
+
+use crate::input::{InputBackend, Key};
+use crate::layout::WindowRect;
+
+/// Handed to a running action; checked between input steps
+pub struct ActionToken {
+    cancelled: Arc<AtomicBool>,
+    deadline: Instant,
+}
+
+impl ActionToken {
+    /// Err once the action was cancelled or ran past its timeout
+    pub fn check(&self) -> Result<(), String> {
+        if self.cancelled.load(Ordering::Relaxed) {
+            return Err("Action cancelled".to_string());
+        }
+        if Instant::now() >= self.deadline {
+            return Err("Action timed out".to_string());
+        }
+        Ok(())
+    }
+}
+
+struct Ticket {
+    id: u64,
+    action: String,
+    enqueued: Instant,
+}
+
+struct Running {
+    id: u64,
+    window_handle: i64,
+    action: String,
+    started: Instant,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct QueueState {
+    next_id: u64,
+    /// Waiting tickets per instance (FIFO)
+    waiting: HashMap<i64, VecDeque<Ticket>>,
+    /// Instances with waiting tickets, in turn order
+    rotation: VecDeque<i64>,
+    running: Option<Running>,
+    /// Tickets removed by cancel_actions whose callers haven't noticed yet
+    cancelled: HashSet<u64>,
+}
+
+impl QueueState {
+    /// Ticket that gets the next turn
+    fn next_id(&self) -> Option<u64> {
+        self.rotation
+            .front()
+            .and_then(|h| self.waiting.get(h))
+            .and_then(|q| q.front())
+            .map(|t| t.id)
+    }
+
+    /// Pop the next ticket and move its instance to the back of the rotation
+    fn grant(&mut self) -> Option<(i64, Ticket)> {
+        let handle = self.rotation.pop_front()?;
+        let queue = self.waiting.get_mut(&handle)?;
+        let ticket = queue.pop_front()?;
+        if queue.is_empty() {
+            self.waiting.remove(&handle);
+        } else {
+            self.rotation.push_back(handle);
+        }
+        Some((handle, ticket))
+    }
+
+    fn remove(&mut self, id: u64) {
+        for queue in self.waiting.values_mut() {
+            queue.retain(|t| t.id != id);
+        }
+        self.waiting.retain(|_, q| !q.is_empty());
+        let waiting = &self.waiting;
+        self.rotation.retain(|h| waiting.contains_key(h));
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueuedAction {
+    pub window_handle: i64,
+    pub action: String,
+    /// Time spent waiting (queued) or running
+    pub elapsed_ms: u64,
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct QueueSnapshot {
+    pub running: Option<QueuedAction>,
+    pub waiting: Vec<QueuedAction>,
+}
+
+#[derive(Default)]
+pub struct Scheduler {
+    state: Mutex<QueueState>,
+    wake: Condvar,
+}
+
+/// Releases the queue when the action finishes (or panics)
+struct Turn<'a> {
+    scheduler: &'a Scheduler,
+    id: u64,
+}
+
+impl Drop for Turn<'_> {
+    fn drop(&mut self) {
+        let mut state = self.scheduler.state.lock().unwrap();
+        if state.running.as_ref().is_some_and(|r| r.id == self.id) {
+            state.running = None;
+        }
+        self.scheduler.wake.notify_all();
+    }
+}
+
+impl Scheduler {
+    /// Wait for this instance's turn, then run `f` exclusively.
+    /// `timeout` bounds both the wait in the queue and the action itself.
+    pub fn run<R>(
+        &self,
+        window_handle: i64,
+        action: &str,
+        timeout: Duration,
+        f: impl FnOnce(&ActionToken) -> Result<R, String>,
+    ) -> Result<R, String> {
+        let id = self.enqueue(window_handle, action);
+        let cancelled = self.wait_turn(id, timeout)?;
+        let _turn = Turn {
+            scheduler: self,
+            id,
+        };
+
+        let token = ActionToken {
+            cancelled,
+            deadline: Instant::now() + timeout,
+        };
+        f(&token)
+    }
+
+    fn enqueue(&self, window_handle: i64, action: &str) -> u64 {
+        let mut state = self.state.lock().unwrap();
+        state.next_id += 1;
+        let id = state.next_id;
+        state
+            .waiting
+            .entry(window_handle)
+            .or_default()
+            .push_back(Ticket {
+                id,
+                action: action.to_string(),
+                enqueued: Instant::now(),
+            });
+        if !state.rotation.contains(&window_handle) {
+            state.rotation.push_back(window_handle);
+        }
+        id
+    }
+
+    fn wait_turn(&self, id: u64, timeout: Duration) -> Result<Arc<AtomicBool>, String> {
+        let deadline = Instant::now() + timeout;
+        let mut state = self.state.lock().unwrap();
+        loop {
+            if state.cancelled.remove(&id) {
+                return Err("Action cancelled while queued".to_string());
+            }
+            if state.running.is_none() && state.next_id() == Some(id) {
+                let (window_handle, ticket) = state.grant().unwrap();
+                let cancelled = Arc::new(AtomicBool::new(false));
+                state.running = Some(Running {
+                    id,
+                    window_handle,
+                    action: ticket.action,
+                    started: Instant::now(),
+                    cancelled: cancelled.clone(),
+                });
+                return Ok(cancelled);
+            }
+
+            let now = Instant::now();
+            if now >= deadline {
+                state.remove(id);
+                self.wake.notify_all();
+                return Err(format!(
+                    "Timed out after {}s waiting in the action queue",
+                    timeout.as_secs()
+                ));
+            }
+            state = self.wake.wait_timeout(state, deadline - now).unwrap().0;
+        }
+    }
+
+    /// Cancel queued and running actions, for one instance or all of them.
+    /// Returns how many actions were cancelled.
+    pub fn cancel(&self, window_handle: Option<i64>) -> usize {
+        let mut state = self.state.lock().unwrap();
+        let mut count = 0;
+
+        let ids: Vec<u64> = state
+            .waiting
+            .iter()
+            .filter(|(h, _)| window_handle.is_none_or(|w| w == **h))
+            .flat_map(|(_, q)| q.iter().map(|t| t.id))
+            .collect();
+        for id in ids {
+            state.remove(id);
+            state.cancelled.insert(id);
+            count += 1;
+        }
+
+        if let Some(ref running) = state.running {
+            if window_handle.is_none_or(|w| w == running.window_handle) {
+                running.cancelled.store(true, Ordering::Relaxed);
+                count += 1;
+            }
+        }
+
+        self.wake.notify_all();
+        count
+    }
+
+    /// Actions waiting for a turn (all instances, or one)
+    pub fn depth(&self, window_handle: Option<i64>) -> usize {
+        let state = self.state.lock().unwrap();
+        match window_handle {
+            Some(h) => state.waiting.get(&h).map_or(0, |q| q.len()),
+            None => state.waiting.values().map(|q| q.len()).sum(),
+        }
+    }
+
+    /// Action currently running for an instance
+    pub fn running_for(&self, window_handle: i64) -> Option<String> {
+        let state = self.state.lock().unwrap();
+        state
+            .running
+            .as_ref()
+            .filter(|r| r.window_handle == window_handle)
+            .map(|r| r.action.clone())
+    }
+
+    pub fn snapshot(&self) -> QueueSnapshot {
+        let state = self.state.lock().unwrap();
+        let running = state.running.as_ref().map(|r| QueuedAction {
+            window_handle: r.window_handle,
+            action: r.action.clone(),
+            elapsed_ms: r.started.elapsed().as_millis() as u64,
+        });
+
+        let mut waiting: Vec<QueuedAction> = state
+            .waiting
+            .iter()
+            .flat_map(|(h, q)| {
+                q.iter().map(move |t| QueuedAction {
+                    window_handle: *h,
+                    action: t.action.clone(),
+                    elapsed_ms: t.enqueued.elapsed().as_millis() as u64,
+                })
+            })
+            .collect();
+        waiting.sort_by_key(|a| std::cmp::Reverse(a.elapsed_ms));
+
+        QueueSnapshot { running, waiting }
+    }
+}
+
+/// Input backend that stops between steps once its action is cancelled or times out
+pub struct GuardedInput<'a> {
+    inner: Box<dyn InputBackend>,
+    token: &'a ActionToken,
+}
+
+impl<'a> GuardedInput<'a> {
+    pub fn new(inner: Box<dyn InputBackend>, token: &'a ActionToken) -> Self {
+        GuardedInput { inner, token }
+    }
+}
+
+impl InputBackend for GuardedInput<'_> {
+    fn focus(&mut self, window_handle: i64) -> Result<bool, String> {
+        self.token.check()?;
+        self.inner.focus(window_handle)
+    }
+
+    fn window_rect(&self, window_handle: i64) -> Option<WindowRect> {
+        self.inner.window_rect(window_handle)
+    }
+
+    fn find_window(&self, title: &str) -> Option<i64> {
+        self.inner.find_window(title)
+    }
+
+    fn move_to(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.move_to(x, y)
+    }
+
+    fn click(&mut self, x: i32, y: i32) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.click(x, y)
+    }
+
+    fn wheel(&mut self, delta: i32) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.wheel(delta)
+    }
+
+    fn key_chord(&mut self, keys: &[Key]) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.key_chord(keys)
+    }
+
+    fn type_text(&mut self, text: &str) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.type_text(text)
+    }
+
+    fn paste(&mut self, text: &str) -> Result<(), String> {
+        self.token.check()?;
+        self.inner.paste(text)
+    }
+
+    fn copy_selection(&mut self) -> Result<Option<String>, String> {
+        self.token.check()?;
+        self.inner.copy_selection()
+    }
+
+    fn sleep(&mut self, ms: u64) {
+        self.inner.sleep(ms)
+    }
+}
+
+/// Running and queued focus-taking actions
+#[tauri::command]
+pub fn get_action_queue(scheduler: tauri::State<'_, Scheduler>) -> QueueSnapshot {
+    scheduler.snapshot()
+}
+
+/// Cancel queued/running actions for an instance (or every instance)
+#[tauri::command]
+pub fn cancel_actions(scheduler: tauri::State<'_, Scheduler>, window_handle: Option<i64>) -> usize {
+    let count = scheduler.cancel(window_handle);
+    println!(
+        "[scheduler] Cancelled {} action(s) for {:?}",
+        count, window_handle
+    );
+    count
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::RecordingInput;
+    use std::sync::mpsc;
+    use std::thread;
+
+    const TIMEOUT: Duration = Duration::from_secs(5);
+
+    fn recorder() -> Box<dyn InputBackend> {
+        Box::new(RecordingInput::new(WindowRect {
+            left: 0,
+            top: 0,
+            right: 800,
+            bottom: 600,
+        }))
+    }
+
+    /// Wait until `depth` actions are queued
+    fn wait_depth(scheduler: &Scheduler, depth: usize) {
+        let deadline = Instant::now() + TIMEOUT;
+        while scheduler.depth(None) < depth {
+            assert!(Instant::now() < deadline, "queue never reached {}", depth);
+            thread::sleep(Duration::from_millis(5));
+        }
+    }
+
+    #[test]
+    fn instances_take_turns() {
+        let scheduler = &Scheduler::default();
+        let order = Mutex::new(Vec::new());
+        let (release, hold) = mpsc::channel::<()>();
+
+        thread::scope(|s| {
+            let (started, running) = mpsc::channel();
+            s.spawn(move || {
+                scheduler.run(1, "a1", TIMEOUT, |_| {
+                    started.send(()).unwrap();
+                    hold.recv().unwrap();
+                    Ok(())
+                })
+            });
+            running.recv().unwrap();
+
+            // Instance 1 queues two actions before instance 2 queues one
+            for (depth, (handle, action)) in
+                [(1, "a2"), (1, "a3"), (2, "b1")].into_iter().enumerate()
+            {
+                let order = &order;
+                s.spawn(move || {
+                    scheduler.run(handle, action, TIMEOUT, |token| {
+                        let mut input = GuardedInput::new(recorder(), token);
+                        input.focus(handle)?;
+                        order.lock().unwrap().push(action);
+                        Ok(())
+                    })
+                });
+                wait_depth(scheduler, depth + 1);
+            }
+            release.send(()).unwrap();
+        });
+
+        assert_eq!(*order.lock().unwrap(), vec!["a2", "b1", "a3"]);
+        assert_eq!(scheduler.depth(None), 0);
+        assert!(scheduler.snapshot().running.is_none());
+    }
+
+    #[test]
+    fn cancel_removes_queued_actions() {
+        let scheduler = &Scheduler::default();
+        let (release, hold) = mpsc::channel::<()>();
+
+        thread::scope(|s| {
+            let (started, running) = mpsc::channel();
+            s.spawn(move || {
+                scheduler.run(1, "a1", TIMEOUT, |_| {
+                    started.send(()).unwrap();
+                    hold.recv().unwrap();
+                    Ok(())
+                })
+            });
+            running.recv().unwrap();
+
+            let queued = s.spawn(move || scheduler.run(2, "b1", TIMEOUT, |_| Ok(())));
+            wait_depth(scheduler, 1);
+
+            assert_eq!(scheduler.cancel(Some(2)), 1);
+            assert_eq!(
+                queued.join().unwrap(),
+                Err("Action cancelled while queued".to_string())
+            );
+            assert_eq!(scheduler.running_for(1), Some("a1".to_string()));
+            release.send(()).unwrap();
+        });
+    }
+
+    #[test]
+    fn cancel_stops_running_action_between_steps() {
+        let scheduler = &Scheduler::default();
+        let (cancelled, wait_cancel) = mpsc::channel::<()>();
+
+        thread::scope(|s| {
+            let (clicked, wait_click) = mpsc::channel();
+            let running = s.spawn(move || {
+                scheduler.run(1, "a1", TIMEOUT, |token| {
+                    let mut input = GuardedInput::new(recorder(), token);
+                    input.click(10, 20)?;
+                    clicked.send(()).unwrap();
+                    wait_cancel.recv().unwrap();
+                    input.paste("hola")
+                })
+            });
+
+            wait_click.recv().unwrap();
+            assert_eq!(scheduler.cancel(None), 1);
+            cancelled.send(()).unwrap();
+            assert_eq!(running.join().unwrap(), Err("Action cancelled".to_string()));
+        });
+        assert!(scheduler.running_for(1).is_none());
+    }
+
+    #[test]
+    fn queued_action_times_out() {
+        let scheduler = &Scheduler::default();
+        let (release, hold) = mpsc::channel::<()>();
+
+        thread::scope(|s| {
+            let (started, running) = mpsc::channel();
+            s.spawn(move || {
+                scheduler.run(1, "a1", TIMEOUT, |_| {
+                    started.send(()).unwrap();
+                    hold.recv().unwrap();
+                    Ok(())
+                })
+            });
+            running.recv().unwrap();
+
+            let result = scheduler.run(2, "b1", Duration::from_millis(50), |_| Ok(()));
+            assert!(result.unwrap_err().starts_with("Timed out"));
+            assert_eq!(scheduler.depth(None), 0);
+            release.send(()).unwrap();
+        });
+    }
+}
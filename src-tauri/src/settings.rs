@@ -0,0 +1,90 @@
+// Frontend settings mirrored into the backend
+// The webview owns the settings (localStorage) and pushes them here with sync_settings
+
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+
+/// Same shape as `Settings` in src/lib/types.ts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct Settings {
+    pub default_prompt: String,
+    pub inactivity_seconds: u32,
+    pub max_retries: u32,
+    pub discord_webhook: String,
+    pub notify_on_complete: bool,
+    pub notify_on_error: bool,
+    pub minimize_to_tray: bool,
+    pub auto_prompt: String,
+    pub poll_interval_seconds: u32,
+    pub stop_conditions: Vec<String>,
+    /// Minutes without a prompt or chat change before an instance times out (0 = off)
+    pub inactivity_timeout_minutes: u32,
+    /// Sent once to a timed-out instance before it is disabled (empty = disable right away)
+    pub inactivity_recovery_prompt: String,
+    /// Minutes without visual change while working before an instance is stalled (0 = off)
+    pub stall_timeout_minutes: u32,
+    pub notify_on_stall: bool,
+    /// Seconds without keyboard/mouse input before BOB may take focus (0 = off)
+    pub user_idle_seconds: u32,
+    /// Max time an action may wait in the queue, and then run
+    pub action_timeout_seconds: u32,
+    /// Log clicks, keys, pastes and notifications instead of performing them
+    pub dry_run: bool,
+    /// Open a new chat after this many prompts in one chat (0 = off)
+    pub new_chat_after_steps: u32,
+    /// Open a new chat when the agent shows signs of context overflow
+    pub new_chat_on_degradation: bool,
+    /// Phrases in the last response that indicate a degraded context
+    pub degradation_phrases: Vec<String>,
+    pub logging_enabled: bool,
+    pub log_file_path: String,
+}
+
+impl Default for Settings {
+    fn default() -> Self {
+        Settings {
+            default_prompt: "Continúa con el siguiente paso".to_string(),
+            inactivity_seconds: 30,
+            max_retries: 3,
+            discord_webhook: String::new(),
+            notify_on_complete: true,
+            notify_on_error: true,
+            minimize_to_tray: true,
+            auto_prompt: String::new(),
+            poll_interval_seconds: 20,
+            stop_conditions: Vec::new(),
+            inactivity_timeout_minutes: 20,
+            inactivity_recovery_prompt: String::new(),
+            stall_timeout_minutes: 15,
+            notify_on_stall: true,
+            user_idle_seconds: 5,
+            action_timeout_seconds: 30,
+            dry_run: false,
+            new_chat_after_steps: 0,
+            new_chat_on_degradation: true,
+            degradation_phrases: vec![
+                "context window".to_string(),
+                "conversation is too long".to_string(),
+                "context limit".to_string(),
+            ],
+            logging_enabled: true,
+            log_file_path: String::new(),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct SettingsStore(Mutex<Settings>);
+
+impl SettingsStore {
+    pub fn get(&self) -> Settings {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// Push the frontend settings to the backend (called on load and on save)
+#[tauri::command]
+pub fn sync_settings(store: tauri::State<'_, SettingsStore>, settings: Settings) {
+    *store.0.lock().unwrap() = settings;
+}
@@ -0,0 +1,204 @@
+// Slash-command prompts ("/implement issue 12")
+// Typing "/" in the agent chat opens an autocomplete popup that swallows
+// Enter, so the command is typed, picked from the popup and read back
+// before the arguments are added
+
+use crate::input::{InputBackend, Key};
+use crate::keymap::{EditorAction, KeybindingProfile};
+use crate::layout::WindowRect;
+
+/// How long to wait for the popup after typing the command
+const POPUP_TIMEOUT_MS: u64 = 2000;
+const POPUP_POLL_MS: u64 = 100;
+
+/// Hash bits that must differ for the popup to count as open
+const POPUP_CHANGE_BITS: u32 = 6;
+
+/// A prompt split into its leading slash command and the rest
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SlashCommand<'a> {
+    /// Command including the slash ("/implement")
+    pub command: &'a str,
+    /// Everything after the command (may be empty). Spaces after the command
+    /// are dropped but a leading line break is kept.
+    pub args: &'a str,
+}
+
+impl<'a> SlashCommand<'a> {
+    /// Split a prompt that starts with "/name" followed by whitespace or the
+    /// end; None for ordinary prompts (including paths like "/src/main.rs")
+    pub fn parse(prompt: &'a str) -> Option<Self> {
+        let prompt = prompt.trim_start();
+        let rest = prompt.strip_prefix('/')?;
+        let name_len = rest
+            .find(|c: char| !(c.is_alphanumeric() || c == '-' || c == '_' || c == ':'))
+            .unwrap_or(rest.len());
+        let args = &rest[name_len..];
+        if name_len == 0 || !(args.is_empty() || args.starts_with(char::is_whitespace)) {
+            return None;
+        }
+        Some(SlashCommand {
+            command: &prompt[..name_len + 1],
+            args: args.trim_start_matches([' ', '\t']).trim_end(),
+        })
+    }
+
+    /// Whether the chat input text starts with exactly this command
+    pub fn matches_input(&self, text: &str) -> bool {
+        text.trim_start()
+            .strip_prefix(self.command)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(char::is_whitespace))
+    }
+}
+
+/// Screen area where the popup opens: above the chat input, across the panel
+fn popup_area(rect: &WindowRect, input_x: i32, input_y: i32) -> WindowRect {
+    let half_width = rect.width() / 5;
+    WindowRect {
+        left: (input_x - half_width).max(rect.left),
+        top: (input_y - rect.height() * 35 / 100).max(rect.top),
+        right: (input_x + half_width).min(rect.right),
+        bottom: (input_y - 20).max(rect.top + 1),
+    }
+}
+
+fn area_hash(area: &WindowRect) -> Option<u64> {
+    crate::frame::capture(area)
+        .ok()
+        .map(|frame| crate::stall::dhash(&frame))
+}
+
+/// Poll the popup area until it visibly changes from `before`
+fn wait_for_popup(input: &mut dyn InputBackend, area: &WindowRect, before: u64) -> bool {
+    let mut waited = 0;
+    while waited < POPUP_TIMEOUT_MS {
+        input.sleep(POPUP_POLL_MS);
+        waited += POPUP_POLL_MS;
+        if area_hash(area).is_some_and(|now| (now ^ before).count_ones() > POPUP_CHANGE_BITS) {
+            return true;
+        }
+    }
+    false
+}
+
+/// Read the chat input text (None when the backend can't copy, e.g. dry runs)
+fn read_input(input: &mut dyn InputBackend) -> Result<Option<String>, String> {
+    input.key_chord(&[Key::CONTROL, Key::char('a')])?;
+    input.sleep(50);
+    let text = input.copy_selection()?;
+    input.key_chord(&[Key::END])?;
+    Ok(text)
+}
+
+/// Type a slash command into the focused chat input, pick it from the
+/// autocomplete popup and append its arguments (without submitting).
+/// `input_point` is where the chat input sits on screen.
+pub fn type_command(
+    input: &mut dyn InputBackend,
+    keys: &KeybindingProfile,
+    rect: &WindowRect,
+    input_point: (i32, i32),
+    slash: &SlashCommand,
+) -> Result<(), String> {
+    let area = popup_area(rect, input_point.0, input_point.1);
+    let before = area_hash(&area);
+
+    // Typed key by key so the editor's autocomplete sees it
+    input.type_text(slash.command)?;
+    let popup = match before {
+        Some(before) => wait_for_popup(input, &area, before),
+        None => false,
+    };
+
+    if popup {
+        input.key_chord(keys.require(EditorAction::AcceptSuggestion)?.keys())?;
+        input.sleep(150);
+    } else {
+        println!(
+            "[slash] No popup seen for {} - keeping the typed text",
+            slash.command
+        );
+    }
+
+    // The highlighted entry may be a longer command sharing the prefix;
+    // fall back to the literal text with the popup dismissed
+    let mut text = read_input(input)?;
+    if let Some(ref current) = text {
+        if !slash.matches_input(current) {
+            println!(
+                "[slash] Popup picked {:?} instead of {} - retyping",
+                current.trim(),
+                slash.command
+            );
+            input.key_chord(&[Key::CONTROL, Key::char('a')])?;
+            input.key_chord(&[Key::BACKSPACE])?;
+            input.type_text(slash.command)?;
+            input.sleep(POPUP_POLL_MS);
+            input.key_chord(&[Key::ESCAPE])?;
+            text = read_input(input)?;
+            if !text.as_deref().is_some_and(|t| slash.matches_input(t)) {
+                return Err(format!(
+                    "Chat input doesn't contain {} after typing it",
+                    slash.command
+                ));
+            }
+        }
+    }
+
+    if !slash.args.is_empty() {
+        // Completion usually leaves a trailing space; add one only if it didn't
+        // and the arguments don't start on their own line
+        let needs_space = !slash.args.starts_with(['\r', '\n'])
+            && !text.as_deref().is_some_and(|t| t.ends_with(' '));
+        let args = if needs_space {
+            format!(" {}", slash.args)
+        } else {
+            slash.args.to_string()
+        };
+        input.paste(&args)?;
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn parses_command_and_args() {
+        let slash = SlashCommand::parse("  /implement  issue 12 ").unwrap();
+        assert_eq!(slash.command, "/implement");
+        assert_eq!(slash.args, "issue 12");
+
+        let slash = SlashCommand::parse("/speckit:plan").unwrap();
+        assert_eq!(slash.command, "/speckit:plan");
+        assert_eq!(slash.args, "");
+    }
+
+    #[test]
+    fn keeps_leading_line_break() {
+        let slash = SlashCommand::parse("/review\nCheck the parser\n").unwrap();
+        assert_eq!(slash.command, "/review");
+        assert_eq!(slash.args, "\nCheck the parser");
+    }
+
+    #[test]
+    fn ordinary_prompts_are_not_commands() {
+        assert_eq!(SlashCommand::parse("/src/main.rs is broken"), None);
+        assert_eq!(SlashCommand::parse("/implement.now"), None);
+        assert_eq!(SlashCommand::parse("/ implement"), None);
+        assert_eq!(SlashCommand::parse("Continue with /implement"), None);
+        assert_eq!(SlashCommand::parse(""), None);
+    }
+
+    #[test]
+    fn matches_input_requires_whole_command() {
+        let slash = SlashCommand::parse("/implement issue 12").unwrap();
+        assert!(slash.matches_input("/implement"));
+        assert!(slash.matches_input(" /implement "));
+        assert!(slash.matches_input("/implement issue"));
+        assert!(!slash.matches_input("/implement-all "));
+        assert!(!slash.matches_input("/impl"));
+        assert!(!slash.matches_input("implement"));
+    }
+}
@@ -0,0 +1,361 @@
+// Stall detection via perceptual frame hashing
+// Each detection hashes the chat region; if the agent is "working" but the
+// chat hasn't visibly changed for too long, the instance is marked stalled
+
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use tauri::Emitter;
+
+use crate::frame::Frame;
+use crate::layout::{LayoutProfile, WindowRect};
+use crate::lifecycle::InstanceState;
+use crate::notify;
+use crate::runtime::{now_ms, Runtime};
+use crate::settings::Settings;
+
+/// Samples kept per instance
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const HISTORY_LEN: usize = 120;
+
+/// Hashes within this many bits count as "no visual change"
+/// (cursor blink, spinner frames)
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const CHANGE_THRESHOLD_BITS: u32 = 4;
+
+/// 64-bit difference hash: 9x8 grid of average brightness, one bit per
+/// horizontal neighbor comparison. Robust to small noise, sensitive to new text.
+pub fn dhash(frame: &Frame) -> u64 {
+    let width = frame.width().max(1) as usize;
+    let height = frame.height().max(1) as usize;
+    let bytes = frame.rgb_bytes();
+
+    let mut cells = [[0f64; 9]; 8];
+    for (row, cells_row) in cells.iter_mut().enumerate() {
+        let y0 = row * height / 8;
+        let y1 = ((row + 1) * height / 8).max(y0 + 1).min(height);
+        for (col, cell) in cells_row.iter_mut().enumerate() {
+            let x0 = col * width / 9;
+            let x1 = ((col + 1) * width / 9).max(x0 + 1).min(width);
+            let mut sum = 0f64;
+            let mut count = 0f64;
+            for y in y0..y1 {
+                for x in x0..x1 {
+                    let i = (y * width + x) * 3;
+                    if i + 2 < bytes.len() {
+                        sum += 0.299 * bytes[i] as f64
+                            + 0.587 * bytes[i + 1] as f64
+                            + 0.114 * bytes[i + 2] as f64;
+                        count += 1.0;
+                    }
+                }
+            }
+            *cell = if count > 0.0 { sum / count } else { 0.0 };
+        }
+    }
+
+    let mut hash = 0u64;
+    for row in &cells {
+        for col in 0..8 {
+            hash = (hash << 1) | (row[col] > row[col + 1]) as u64;
+        }
+    }
+    hash
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisualSample {
+    pub timestamp: u64,
+    pub hash: String,
+    /// Bits changed since the last visual change
+    pub distance: u32,
+    pub working: bool,
+}
+
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct VisualHistory {
+    /// Hash at the last visual change (compared against, so slow drift still counts)
+    #[serde(skip)]
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    reference_hash: Option<u64>,
+    pub last_visual_change: u64,
+    pub stalled: bool,
+    pub samples: VecDeque<VisualSample>,
+}
+
+#[derive(Debug, PartialEq)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub enum StallTransition {
+    None,
+    Stalled { idle_ms: u64 },
+    Recovered,
+}
+
+impl VisualHistory {
+    /// Record a new hash. `timeout_ms` of 0 disables stall detection.
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn observe(
+        &mut self,
+        hash: u64,
+        now: u64,
+        working: bool,
+        timeout_ms: u64,
+    ) -> StallTransition {
+        let distance = self
+            .reference_hash
+            .map(|h| (h ^ hash).count_ones())
+            .unwrap_or(64);
+
+        if distance > CHANGE_THRESHOLD_BITS {
+            self.reference_hash = Some(hash);
+            self.last_visual_change = now;
+        }
+
+        self.samples.push_back(VisualSample {
+            timestamp: now,
+            hash: format!("{:016x}", hash),
+            distance,
+            working,
+        });
+        while self.samples.len() > HISTORY_LEN {
+            self.samples.pop_front();
+        }
+
+        let idle_ms = now.saturating_sub(self.last_visual_change);
+        if working && timeout_ms > 0 && idle_ms >= timeout_ms {
+            if !self.stalled {
+                self.stalled = true;
+                return StallTransition::Stalled { idle_ms };
+            }
+        } else if self.stalled {
+            self.stalled = false;
+            return StallTransition::Recovered;
+        }
+        StallTransition::None
+    }
+}
+
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+struct StallEvent {
+    window_handle: i64,
+    project_name: Option<String>,
+    stalled: bool,
+    last_visual_change: u64,
+}
+
+/// Hash the chat region and update the instance's visual history.
+/// Raises `instance-stalled` events (and optionally Discord) on transitions.
+/// `dry_run` sends the Discord message to the log instead.
+#[allow(clippy::too_many_arguments)]
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+pub fn track(
+    app: &tauri::AppHandle,
+    runtime: &Runtime,
+    settings: &Settings,
+    window_handle: i64,
+    project_name: Option<&str>,
+    rect: &WindowRect,
+    layout: &LayoutProfile,
+    working: bool,
+    dry_run: bool,
+) {
+    let (x0, y0, x1, y1) = layout.chat_area().to_pixels(rect.width(), rect.height());
+    let region = WindowRect {
+        left: rect.left + x0,
+        top: rect.top + y0,
+        right: rect.left + x1,
+        bottom: rect.top + y1,
+    };
+    let frame = match crate::frame::capture(&region) {
+        Ok(frame) => frame,
+        Err(e) => {
+            println!("[stall] Skipping visual hash: {}", e);
+            return;
+        }
+    };
+
+    let hash = dhash(&frame);
+    let timeout_ms = settings.stall_timeout_minutes as u64 * 60 * 1000;
+    let (transition, last_visual_change) = runtime.with(window_handle, |rt| {
+        if project_name.is_some() {
+            rt.project_name = project_name.map(str::to_string);
+        }
+        let mut transition = rt.visual.observe(hash, now_ms(), working, timeout_ms);
+        let state = match transition {
+            StallTransition::None => Ok(false),
+            StallTransition::Stalled { idle_ms } => rt.state.transition(
+                InstanceState::Stalled,
+                &format!("No visual change for {} min while working", idle_ms / 60000),
+            ),
+            // Only a stalled instance goes back to work; a paused, disabled
+            // or idle one stays where the supervisor or user put it
+            StallTransition::Recovered if rt.state.state == InstanceState::Stalled => rt
+                .state
+                .transition(InstanceState::Working, "Chat changed again"),
+            StallTransition::Recovered => Ok(false),
+        };
+        if let Err(e) = state {
+            println!("[stall] {}", e);
+            // Not actually stalled, so there is nothing to recover from later
+            if let StallTransition::Stalled { .. } = transition {
+                rt.visual.stalled = false;
+                transition = StallTransition::None;
+            }
+        }
+        (transition, rt.visual.last_visual_change)
+    });
+
+    let name = project_name.unwrap_or("window").to_string();
+    let stalled = match transition {
+        StallTransition::None => return,
+        StallTransition::Stalled { idle_ms } => {
+            let minutes = idle_ms / 60000;
+            notify::log(
+                settings,
+                "WARN",
+                &format!(
+                    "[{}] Stalled: no visual change for {} min while working",
+                    name, minutes
+                ),
+            );
+            if settings.notify_on_stall {
+                notify::spawn_discord(
+                    settings,
+                    dry_run,
+                    format!("🧊 {} sin cambios", name),
+                    format!(
+                        "El agente aparece trabajando pero la pantalla no cambia hace {} minutos. Puede estar colgado.",
+                        minutes
+                    ),
+                );
+            }
+            true
+        }
+        StallTransition::Recovered => {
+            notify::log(
+                settings,
+                "INFO",
+                &format!("[{}] Recovered from stall", name),
+            );
+            false
+        }
+    };
+
+    let _ = app.emit(
+        "instance-stalled",
+        StallEvent {
+            window_handle,
+            project_name: project_name.map(str::to_string),
+            stalled,
+            last_visual_change,
+        },
+    );
+}
+
+/// Visual change history for an instance
+#[tauri::command]
+pub fn get_visual_history(
+    runtime: tauri::State<'_, Runtime>,
+    window_handle: i64,
+) -> Option<VisualHistory> {
+    runtime.get(window_handle).map(|rt| rt.visual)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MINUTE: u64 = 60 * 1000;
+
+    fn rect() -> WindowRect {
+        WindowRect {
+            left: 0,
+            top: 0,
+            right: 90,
+            bottom: 80,
+        }
+    }
+
+    #[test]
+    fn dhash_ignores_identical_frames() {
+        let a = Frame::filled(rect(), (30, 30, 30));
+        let b = Frame::filled(rect(), (30, 30, 30));
+        assert_eq!(dhash(&a), dhash(&b));
+    }
+
+    #[test]
+    fn dhash_sees_new_content() {
+        let blank = Frame::filled(rect(), (30, 30, 30));
+        let mut text = Frame::filled(rect(), (30, 30, 30));
+        // Lines of bright "text" in every other grid column
+        for y in 10..70 {
+            for x in (0..90).filter(|x| (x / 10) % 2 == 0) {
+                text.set(x, y, (240, 240, 240));
+            }
+        }
+        let distance = (dhash(&blank) ^ dhash(&text)).count_ones();
+        assert!(distance > CHANGE_THRESHOLD_BITS, "distance {}", distance);
+    }
+
+    #[test]
+    fn stalls_once_timeout_is_reached() {
+        let mut history = VisualHistory::default();
+        assert_eq!(
+            history.observe(1, 0, true, 5 * MINUTE),
+            StallTransition::None
+        );
+        assert_eq!(
+            history.observe(1, 4 * MINUTE, true, 5 * MINUTE),
+            StallTransition::None
+        );
+        assert_eq!(
+            history.observe(1, 5 * MINUTE, true, 5 * MINUTE),
+            StallTransition::Stalled {
+                idle_ms: 5 * MINUTE
+            }
+        );
+        assert!(history.stalled);
+        // Reported once
+        assert_eq!(
+            history.observe(1, 6 * MINUTE, true, 5 * MINUTE),
+            StallTransition::None
+        );
+    }
+
+    #[test]
+    fn zero_timeout_never_stalls() {
+        let mut history = VisualHistory::default();
+        history.observe(1, 0, true, 0);
+        assert_eq!(
+            history.observe(1, 600 * MINUTE, true, 0),
+            StallTransition::None
+        );
+        assert!(!history.stalled);
+    }
+
+    #[test]
+    fn recovers_only_after_a_stall() {
+        let mut history = VisualHistory::default();
+        history.observe(0, 0, true, 5 * MINUTE);
+        // A change without a stall is just a change
+        assert_eq!(
+            history.observe(u64::MAX, MINUTE, true, 5 * MINUTE),
+            StallTransition::None
+        );
+        // Small noise doesn't count as a change
+        assert_eq!(
+            history.observe(u64::MAX ^ 0b111, 7 * MINUTE, true, 5 * MINUTE),
+            StallTransition::Stalled {
+                idle_ms: 6 * MINUTE
+            }
+        );
+        assert_eq!(
+            history.observe(0, 8 * MINUTE, true, 5 * MINUTE),
+            StallTransition::Recovered
+        );
+        assert!(!history.stalled);
+    }
+}
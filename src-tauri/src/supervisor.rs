@@ -0,0 +1,1149 @@
+// Auto-implementation supervisor
+// Owns the polling loop that drives enabled instances: read the backlog,
+// detect the UI state and decide whether to accept, retry, open a new chat or
+// send the next prompt. It runs on the async runtime, so it keeps going while
+// the webview is hidden or suspended; every decision is emitted to the
+// frontend as a `supervisor-update` event.
+
+use chrono::TimeZone;
+use serde::{Deserialize, Serialize};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter, Manager};
+
+use crate::actions::{self, ActionContext, ActionOutcome, ActionStage};
+use crate::budget::{self, OverBudgetAction};
+use crate::carryover;
+use crate::config::ConfigStore;
+use crate::detect::ElementKind;
+use crate::inactivity::{self, Verdict};
+use crate::keymap::EditorAction;
+use crate::lifecycle::{self, InstanceState};
+use crate::notify;
+use crate::playbook;
+use crate::response;
+use crate::retry;
+use crate::rules::{self, RuleAction, RuleFiring};
+use crate::runtime::{self, Runtime};
+use crate::schedule::{self, Window};
+use crate::settings::{Settings, SettingsStore};
+use crate::{BacklogResult, UIStateResult};
+
+/// Longest wait for the backlog reader script before detection goes ahead
+const BACKLOG_TIMEOUT: Duration = Duration::from_secs(20);
+
+/// An instance handed to the supervisor by the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisedInstance {
+    pub window_handle: i64,
+    pub project_name: String,
+    pub project_path: String,
+    #[serde(default)]
+    pub custom_prompt: Option<String>,
+    #[serde(default)]
+    pub backlog_path: Option<String>,
+    #[serde(default)]
+    pub backlog_mode: Option<String>,
+    pub enabled: bool,
+}
+
+/// Result of one supervisor decision, streamed to the frontend
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisorUpdate {
+    pub window_handle: i64,
+    pub project_name: String,
+    /// What the supervisor did or why it waited
+    pub message: String,
+    /// Lifecycle state after the decision
+    pub status: InstanceState,
+    pub enabled: bool,
+    /// Back in the loop after a retry cooldown
+    pub resumed: bool,
+    /// Why the instance was taken out of the loop
+    pub block_reason: Option<String>,
+    pub step_count: u32,
+    pub retry_count: u32,
+    pub last_activity: u64,
+    pub last_prompt_sent: u64,
+    pub last_response: Option<String>,
+    pub backlog: Option<BacklogResult>,
+    pub ui_state: Option<UIStateResult>,
+}
+
+/// Loop state and the instances it drives
+#[derive(Debug, Clone, Default, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SupervisorState {
+    pub running: bool,
+    pub instances: Vec<SupervisedInstance>,
+    /// Completed polling cycles since the last start
+    pub cycles: u64,
+    pub last_cycle_at: u64,
+    /// Bumped on every start and stop so a loop from an earlier start exits
+    #[serde(skip)]
+    generation: u64,
+}
+
+#[derive(Default)]
+pub struct Supervisor(Mutex<SupervisorState>);
+
+impl Supervisor {
+    /// Replace the supervised instances. Returns the loop generation to spawn
+    /// if the supervisor wasn't already running.
+    fn start(&self, instances: Vec<SupervisedInstance>) -> Option<u64> {
+        let mut state = self.0.lock().unwrap();
+        state.instances = instances;
+        if state.running {
+            return None;
+        }
+        state.running = true;
+        state.cycles = 0;
+        state.generation += 1;
+        Some(state.generation)
+    }
+
+    fn stop(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.running = false;
+        state.generation += 1;
+    }
+
+    fn is_current(&self, generation: u64) -> bool {
+        let state = self.0.lock().unwrap();
+        state.running && state.generation == generation
+    }
+
+    fn enabled(&self) -> Vec<SupervisedInstance> {
+        let state = self.0.lock().unwrap();
+        state
+            .instances
+            .iter()
+            .filter(|i| i.enabled)
+            .cloned()
+            .collect()
+    }
+
+    fn disabled(&self) -> Vec<SupervisedInstance> {
+        let state = self.0.lock().unwrap();
+        state
+            .instances
+            .iter()
+            .filter(|i| !i.enabled)
+            .cloned()
+            .collect()
+    }
+
+    fn is_enabled(&self, window_handle: i64) -> bool {
+        let state = self.0.lock().unwrap();
+        state
+            .instances
+            .iter()
+            .any(|i| i.window_handle == window_handle && i.enabled)
+    }
+
+    fn upsert(&self, instance: SupervisedInstance) {
+        let mut state = self.0.lock().unwrap();
+        match state
+            .instances
+            .iter_mut()
+            .find(|i| i.window_handle == instance.window_handle)
+        {
+            Some(existing) => *existing = instance,
+            None => state.instances.push(instance),
+        }
+    }
+
+    fn set_enabled(&self, window_handle: i64, enabled: bool) {
+        let mut state = self.0.lock().unwrap();
+        for instance in state
+            .instances
+            .iter_mut()
+            .filter(|i| i.window_handle == window_handle)
+        {
+            instance.enabled = enabled;
+        }
+    }
+
+    fn finish_cycle(&self) {
+        let mut state = self.0.lock().unwrap();
+        state.cycles += 1;
+        state.last_cycle_at = runtime::now_ms();
+    }
+
+    pub fn snapshot(&self) -> SupervisorState {
+        self.0.lock().unwrap().clone()
+    }
+}
+
+/// What the supervisor did with an instance in one cycle
+#[derive(Debug, Default)]
+struct Decision {
+    message: String,
+    status: Option<InstanceState>,
+    /// Set when the instance is disabled and taken out of the loop
+    stopped: Option<String>,
+    /// Set when the supervisor put a disabled instance back in the loop
+    resumed: bool,
+    ui_state: Option<UIStateResult>,
+}
+
+impl Decision {
+    fn note(message: impl Into<String>) -> Self {
+        Decision {
+            message: message.into(),
+            ..Decision::default()
+        }
+    }
+
+    fn status(message: impl Into<String>, status: InstanceState) -> Self {
+        Decision {
+            message: message.into(),
+            status: Some(status),
+            ..Decision::default()
+        }
+    }
+
+    fn stop(message: impl Into<String>, status: InstanceState, reason: impl Into<String>) -> Self {
+        Decision {
+            message: message.into(),
+            status: Some(status),
+            stopped: Some(reason.into()),
+            ..Decision::default()
+        }
+    }
+}
+
+/// Poll every enabled instance until stopped, then wait the poll interval
+async fn run_loop(app: AppHandle, generation: u64) {
+    let supervisor = app.state::<Supervisor>();
+    while supervisor.is_current(generation) {
+        resume_cooled_down(&app);
+        for instance in supervisor.enabled() {
+            if !supervisor.is_current(generation) {
+                return;
+            }
+            // The user may have disabled it earlier in this cycle
+            if !supervisor.is_enabled(instance.window_handle) {
+                continue;
+            }
+
+            if let Some(decision) = check_schedule(&app, &instance) {
+                publish(&app, &instance, decision);
+                continue;
+            }
+            let decision = cycle(&app, &instance).await;
+            if decision.stopped.is_some() {
+                supervisor.set_enabled(instance.window_handle, false);
+            }
+            publish(&app, &instance, decision);
+        }
+        supervisor.finish_cycle();
+
+        let interval = app
+            .state::<SettingsStore>()
+            .get()
+            .poll_interval_seconds
+            .max(1);
+        tokio::time::sleep(Duration::from_secs(interval as u64)).await;
+    }
+}
+
+/// Give instances that ran out of retries a fresh budget once their cooldown ends
+fn resume_cooled_down(app: &AppHandle) {
+    let supervisor = app.state::<Supervisor>();
+    let runtime = app.state::<Runtime>();
+    let now = runtime::now_ms();
+    for instance in supervisor.disabled() {
+        let resumed = runtime.with(instance.window_handle, |rt| {
+            let due = retry::cooled_down(rt, now);
+            if due {
+                retry::reset(rt);
+            }
+            due
+        });
+        if !resumed {
+            continue;
+        }
+        supervisor.set_enabled(instance.window_handle, true);
+        let mut decision = Decision::status(
+            "Retry cooldown over - resuming with a fresh retry budget",
+            InstanceState::Idle,
+        );
+        decision.resumed = true;
+        publish(app, &instance, decision);
+    }
+}
+
+/// Hold an instance outside its run windows; resume it when one opens
+fn check_schedule(app: &AppHandle, instance: &SupervisedInstance) -> Option<Decision> {
+    let window_handle = instance.window_handle;
+    let schedules = app
+        .state::<ConfigStore>()
+        .get()
+        .schedules_for(Some(&instance.project_name));
+    let runtime = app.state::<Runtime>();
+
+    // A schedule that doesn't parse must not hold the run silently
+    let window = schedule::window(&schedules, chrono::Local::now()).unwrap_or_else(|e| {
+        notify::log(
+            &app.state::<SettingsStore>().get(),
+            "ERROR",
+            &format!("[{}] Ignoring schedule: {}", instance.project_name, e),
+        );
+        Window::Open
+    });
+
+    match window {
+        Window::Open => {
+            let state = runtime.with(window_handle, |rt| {
+                rt.next_run_at = 0;
+                rt.state.state
+            });
+            if state == InstanceState::OffHours {
+                lifecycle::apply(
+                    &runtime,
+                    window_handle,
+                    InstanceState::Idle,
+                    "Schedule window opened",
+                );
+            }
+            None
+        }
+        Window::Closed { next_run } => {
+            runtime.with(window_handle, |rt| rt.next_run_at = next_run);
+            let when = match chrono::Local.timestamp_millis_opt(next_run as i64).single() {
+                Some(at) if next_run > 0 => at.format("%a %H:%M").to_string(),
+                _ => "never".to_string(),
+            };
+            Some(Decision::status(
+                format!("Outside schedule - next run {}", when),
+                InstanceState::OffHours,
+            ))
+        }
+    }
+}
+
+/// One pass over an instance: timeout and completion checks, then detection and action
+async fn cycle(app: &AppHandle, instance: &SupervisedInstance) -> Decision {
+    let settings = app.state::<SettingsStore>().get();
+
+    if let Some(decision) = check_inactivity(app, instance, &settings).await {
+        return decision;
+    }
+
+    if let Some(backlog) = refresh_backlog(app, instance).await {
+        if backlog.total_issues > 0 && backlog.completed_issues >= backlog.total_issues {
+            if settings.notify_on_complete {
+                send_notification(
+                    app,
+                    instance,
+                    &settings,
+                    format!("🎉 {} Completado!", instance.project_name),
+                    format!(
+                        "Todos los issues han sido completados ({}/{}). El proyecto ha sido deshabilitado automáticamente.",
+                        backlog.completed_issues, backlog.total_issues
+                    ),
+                );
+            }
+            return Decision::stop(
+                format!(
+                    "All issues completed ({}/{})",
+                    backlog.completed_issues, backlog.total_issues
+                ),
+                InstanceState::Complete,
+                "All issues completed",
+            );
+        }
+
+        let config = app.state::<ConfigStore>().get();
+        let firings: Vec<RuleFiring> = rules::rules_for(&config, &settings, &instance.project_name)
+            .iter()
+            .filter_map(|rule| {
+                let matched = rule.match_backlog(&backlog)?;
+                let context = backlog.completed_issues.max(0) as u64;
+                rules::record(
+                    &app.state::<Runtime>(),
+                    instance.window_handle,
+                    rule,
+                    matched,
+                    context,
+                )
+            })
+            .collect();
+        if let Some(decision) = apply_firings(app, instance, &settings, firings) {
+            return decision;
+        }
+    }
+
+    // Detection and input block on the action queue
+    let app = app.clone();
+    let instance = instance.clone();
+    tauri::async_runtime::spawn_blocking(move || act(&app, &instance, &settings))
+        .await
+        .unwrap_or_else(|e| Decision::note(format!("Supervisor task failed: {}", e)))
+}
+
+/// Nudge or time out an instance that has shown no activity for too long
+async fn check_inactivity(
+    app: &AppHandle,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+) -> Option<Decision> {
+    let rt = app
+        .state::<Runtime>()
+        .get(instance.window_handle)
+        .unwrap_or_default();
+
+    match inactivity::check(settings, &rt, runtime::now_ms()) {
+        Verdict::Active => None,
+        Verdict::Recover { idle_ms } => {
+            let app = app.clone();
+            let instance = instance.clone();
+            let settings = settings.clone();
+            let recover = tauri::async_runtime::spawn_blocking(move || {
+                actions::with_context(&app, |ctx| {
+                    send_recovery_prompt(&app, ctx, &instance, &settings, idle_ms)
+                })
+            });
+            Some(
+                recover
+                    .await
+                    .unwrap_or_else(|e| Decision::note(format!("Supervisor task failed: {}", e))),
+            )
+        }
+        Verdict::TimedOut { idle_ms } => {
+            let minutes = idle_ms / 60000;
+            let recovered = if rt.recovery_prompt_at > 0 {
+                " (el prompt de recuperación no tuvo efecto)"
+            } else {
+                ""
+            };
+            if settings.notify_on_error {
+                send_notification(
+                    app,
+                    instance,
+                    settings,
+                    format!("⏰ {} Detenido por Inactividad", instance.project_name),
+                    format!(
+                        "Sin prompts ni cambios en el chat durante {} minutos{}. El proyecto ha sido deshabilitado automáticamente.",
+                        minutes, recovered
+                    ),
+                );
+            }
+            Some(Decision::stop(
+                format!("Inactivity timeout: no activity for {} minutes", minutes),
+                InstanceState::TimedOut,
+                format!("Inactivity timeout: {} min", minutes),
+            ))
+        }
+    }
+}
+
+/// One nudge before timing out; a regular prompt re-arms it. The nudge
+/// counts against the budget like any other prompt.
+fn send_recovery_prompt(
+    app: &AppHandle,
+    ctx: &ActionContext,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    idle_ms: u64,
+) -> Decision {
+    let window_handle = instance.window_handle;
+    if let Some(decision) = enforce_budget(app, ctx, instance, settings) {
+        return decision;
+    }
+    notify::log(
+        &ctx.settings.get(),
+        "WARN",
+        &format!(
+            "[{}] No activity for {} min - sending recovery prompt",
+            instance.project_name,
+            idle_ms / 60000
+        ),
+    );
+    let outcome = actions::write_prompt(
+        ctx,
+        window_handle,
+        &settings.inactivity_recovery_prompt,
+        Some(&instance.project_name),
+    );
+    let message = format!(
+        "No activity for {} min - sent recovery prompt{}",
+        idle_ms / 60000,
+        describe(&outcome)
+    );
+    // Not sent: the nudge is still owed, so try again next cycle
+    if outcome.stage < ActionStage::InputSent {
+        return Decision::note(message);
+    }
+    ctx.runtime.with(window_handle, |rt| {
+        rt.recovery_prompt_at = runtime::now_ms();
+        rt.last_activity = rt.recovery_prompt_at;
+        budget::record_prompt(rt, rt.last_activity);
+    });
+    Decision::status(message, InstanceState::Working)
+}
+
+/// Read the instance's backlog; read errors and timeouts don't stop detection
+async fn refresh_backlog(app: &AppHandle, instance: &SupervisedInstance) -> Option<BacklogResult> {
+    let app = app.clone();
+    let window_handle = instance.window_handle;
+    let project_path = instance.project_path.clone();
+    let backlog_path = instance.backlog_path.clone();
+    let mode = instance.backlog_mode.clone();
+    let read = tauri::async_runtime::spawn_blocking(move || {
+        crate::load_backlog(
+            &app.state::<Runtime>(),
+            project_path,
+            backlog_path,
+            mode,
+            Some(window_handle),
+        )
+    });
+
+    match tokio::time::timeout(BACKLOG_TIMEOUT, read).await {
+        Ok(Ok(Ok(backlog))) if backlog.error.is_none() => Some(backlog),
+        _ => None,
+    }
+}
+
+/// Detect the instance's UI state and act on it
+fn act(app: &AppHandle, instance: &SupervisedInstance, settings: &Settings) -> Decision {
+    actions::with_context(app, |ctx| {
+        let name = Some(instance.project_name.as_str());
+        let ui = match crate::detect_instance(app, ctx, instance.window_handle, name) {
+            Ok(ui) => ui,
+            Err(e) => return Decision::note(format!("Detection failed: {}", e)),
+        };
+        let mut decision = decide(app, ctx, instance, settings, &ui);
+        decision.ui_state = Some(ui);
+        decision
+    })
+}
+
+/// The decision tree for one detected UI state
+fn decide(
+    app: &AppHandle,
+    ctx: &ActionContext,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    ui: &UIStateResult,
+) -> Decision {
+    let window_handle = instance.window_handle;
+    let name = Some(instance.project_name.as_str());
+
+    // Don't act on a transient frame (tooltip, half-rendered button)
+    if !ui.stable && ui.error.is_none() {
+        return Decision::note(format!(
+            "Detection not stable ({} agreeing frames) - waiting",
+            ui.agreeing_frames
+        ));
+    }
+
+    // Accept all in the bottom bar takes priority
+    if ui.has_accept_button && ui.is_bottom_button {
+        let outcome = actions::click(
+            ctx,
+            window_handle,
+            ElementKind::Accept,
+            ui.accept_button_x,
+            ui.accept_button_y,
+            name,
+        );
+        touch(ctx, window_handle, true);
+        return Decision::note(format!("Clicked Accept all{}", describe(&outcome)));
+    }
+
+    let has_dialog = ui.has_accept_button && !ui.is_bottom_button;
+
+    // The pause square means the agent is working even if the corner button
+    // reads gray (a dialog may cover it)
+    if ui.is_paused && ui.chat_button_color != "red" {
+        if has_dialog {
+            return accept_dialog(ctx, window_handle, name);
+        }
+        return Decision::note("Agent working (paused) - waiting");
+    }
+
+    match ui.chat_button_color.as_str() {
+        // Unreadable pixels (non-primary monitor): can't tell working from idle
+        "none" => Decision::note(format!(
+            "Pixel detection unreliable - skipping ({})",
+            ui.error.as_deref().unwrap_or("unknown")
+        )),
+        "gray" => chat_ready(app, ctx, instance, settings, ui),
+        "red" if has_dialog => accept_dialog(ctx, window_handle, name),
+        "red" => Decision::note("Agent working - waiting"),
+        _ if has_dialog => accept_dialog(ctx, window_handle, name),
+        // Scroll stuck away from the bottom hides the chat controls
+        _ => {
+            let outcome = actions::scroll(ctx, window_handle, name);
+            Decision::note(format!(
+                "Unknown state ({}) - scrolled to bottom{}",
+                ui.chat_button_color,
+                describe(&outcome)
+            ))
+        }
+    }
+}
+
+/// Chat is idle: retry a failed step, stop on a stop condition, reset the
+/// context when due, or send the next prompt
+fn chat_ready(
+    app: &AppHandle,
+    ctx: &ActionContext,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    ui: &UIStateResult,
+) -> Decision {
+    let window_handle = instance.window_handle;
+    let name = Some(instance.project_name.as_str());
+    let rt = ctx.runtime.get(window_handle).unwrap_or_default();
+
+    if ui.has_retry_button {
+        return retry(app, ctx, instance, settings, ui);
+    }
+
+    if !ui.has_enter_button {
+        return Decision::note("Chat idle but no input detected - waiting");
+    }
+
+    // Check the last response against the rules before sending the next prompt
+    if rt.step_count > 0 {
+        match response::read_response(ctx, window_handle, name) {
+            Ok(text) => {
+                let firings: Vec<RuleFiring> =
+                    rules::rules_for(&ctx.config.get(), settings, &instance.project_name)
+                        .iter()
+                        .filter(|rule| !rule.is_backlog())
+                        .filter_map(|rule| {
+                            let matched = rule.match_text(&text)?;
+                            rules::record(
+                                ctx.runtime,
+                                window_handle,
+                                rule,
+                                matched,
+                                rt.last_prompt_at,
+                            )
+                        })
+                        .collect();
+                if let Some(decision) = apply_firings(app, instance, settings, firings) {
+                    return decision;
+                }
+            }
+            Err(e) => println!(
+                "[supervisor] [{}] Could not read the last response: {}",
+                instance.project_name, e
+            ),
+        }
+    }
+
+    // A rule's canned reply goes out instead of the auto prompt
+    let reply = ctx
+        .runtime
+        .with(window_handle, |rt| rt.pending_reply.take());
+
+    if rt.step_count > 0 && reply.is_none() {
+        // Context reset: open a new chat instead when one is due and the
+        // editor has a binding for it; otherwise send the normal prompt
+        let rt = ctx.runtime.get(window_handle).unwrap_or_default();
+        let bound = ctx
+            .config
+            .get()
+            .keybindings_for(name)
+            .chord(EditorAction::NewChat)
+            .is_some();
+        if let Some(reason) = carryover::due(settings, &rt).filter(|_| bound) {
+            let outcome = actions::open_new_chat(ctx, window_handle, name, Some(&reason), &[]);
+            touch(ctx, window_handle, false);
+            let message = format!("Opened new chat ({}){}", reason, describe(&outcome));
+            if outcome.success {
+                return Decision::status(message, InstanceState::Working);
+            }
+            println!("[supervisor] [{}] {}", instance.project_name, message);
+        }
+    }
+
+    if let Some(decision) = enforce_budget(app, ctx, instance, settings) {
+        return decision;
+    }
+
+    // Reply, else the playbook step, else the instance's or the auto prompt
+    let playbook = ctx.config.get().playbook_for(name);
+    let step = match (&reply, playbook) {
+        (None, Some(playbook)) => Some(
+            ctx.runtime
+                .with(window_handle, |rt| playbook::next_prompt(&playbook, rt)),
+        ),
+        (_, None) => {
+            ctx.runtime.with(window_handle, |rt| rt.playbook = None);
+            None
+        }
+        _ => None,
+    };
+    let prompt = reply.as_deref().or(step.as_deref()).unwrap_or_else(|| {
+        instance
+            .custom_prompt
+            .as_deref()
+            .filter(|p| !p.is_empty())
+            .unwrap_or(&settings.auto_prompt)
+    });
+    let outcome = actions::write_prompt(ctx, window_handle, prompt, name);
+    let message = format!(
+        "Sent prompt: \"{}\"{}",
+        prompt.chars().take(50).collect::<String>(),
+        describe(&outcome)
+    );
+    // Nothing reached the chat: leave the counters alone and try again next cycle
+    if outcome.stage < ActionStage::InputSent {
+        touch(ctx, window_handle, false);
+        return Decision::note(message);
+    }
+    ctx.runtime.with(window_handle, |rt| {
+        rt.step_count += 1;
+        rt.last_activity = runtime::now_ms();
+        rt.recovery_prompt_at = 0;
+        retry::succeeded(rt);
+        budget::record_prompt(rt, rt.last_activity);
+        if step.is_some() {
+            playbook::mark_sent(rt);
+        }
+    });
+    Decision::status(message, InstanceState::Working)
+}
+
+/// Check the instance's budgets before the next prompt; pause it, skip the
+/// issue or notify when one is reached
+fn enforce_budget(
+    app: &AppHandle,
+    ctx: &ActionContext,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+) -> Option<Decision> {
+    let window_handle = instance.window_handle;
+    let policy = ctx.config.get().budget_for(Some(&instance.project_name));
+    let now = runtime::now_ms();
+    let over = ctx
+        .runtime
+        .with(window_handle, |rt| budget::check(&policy, rt, now))?;
+
+    let title = format!("💰 {} - Presupuesto Excedido", instance.project_name);
+    let notify = |message: String| {
+        if settings.notify_on_error || over.action == OverBudgetAction::Notify {
+            send_notification(app, instance, settings, title.clone(), message);
+        }
+    };
+    notify::log(
+        settings,
+        "WARN",
+        &format!(
+            "[{}] Over budget ({:?}): {}",
+            instance.project_name, over.action, over.detail
+        ),
+    );
+
+    match over.action {
+        OverBudgetAction::Notify => {
+            notify(format!(
+                "Límite alcanzado: {}. El proyecto sigue activo.",
+                over.detail
+            ));
+            None
+        }
+        OverBudgetAction::SkipIssue if !over.already_skipped => {
+            let issue = ctx
+                .runtime
+                .with(window_handle, |rt| rt.budget.issue.clone());
+            let outcome = actions::write_prompt(
+                ctx,
+                window_handle,
+                &policy.skip_prompt,
+                Some(&instance.project_name),
+            );
+            let message = format!(
+                "Over budget ({}) - skipping issue {}{}",
+                over.detail,
+                issue,
+                describe(&outcome)
+            );
+            // Only a skip prompt that reached the chat marks the issue skipped
+            if outcome.stage < ActionStage::InputSent {
+                touch(ctx, window_handle, false);
+                return Some(Decision::note(message));
+            }
+            ctx.runtime.with(window_handle, |rt| {
+                budget::record_skip(rt);
+                let blocker = format!("{}: over budget ({})", issue, over.detail);
+                if !rt.blockers.contains(&blocker) {
+                    rt.blockers.push(blocker);
+                }
+                rt.last_activity = now;
+                budget::record_prompt(rt, now);
+            });
+            notify(format!(
+                "Límite alcanzado: {}. Se salta el issue {} y se marca como bloqueado.",
+                over.detail, issue
+            ));
+            Some(Decision::status(message, InstanceState::Working))
+        }
+        // Pause, or the skip didn't move the agent on: stop instead of looping
+        OverBudgetAction::Pause | OverBudgetAction::SkipIssue => {
+            notify(format!(
+                "Límite alcanzado: {}. El proyecto ha sido pausado.",
+                over.detail
+            ));
+            Some(Decision::stop(
+                format!("Over budget: {}", over.detail),
+                InstanceState::Paused,
+                format!("Budget: {}", over.detail),
+            ))
+        }
+    }
+}
+
+/// Accept the dialog the agent is waiting on
+fn accept_dialog(ctx: &ActionContext, window_handle: i64, project_name: Option<&str>) -> Decision {
+    let outcome = actions::accept(ctx, window_handle, project_name);
+    touch(ctx, window_handle, true);
+    Decision::note(format!("Accepted dialog{}", describe(&outcome)))
+}
+
+/// Record that the supervisor acted on the instance
+fn touch(ctx: &ActionContext, window_handle: i64, reset_retries: bool) {
+    ctx.runtime.with(window_handle, |rt| {
+        rt.last_activity = runtime::now_ms();
+        if reset_retries {
+            retry::succeeded(rt);
+        }
+    });
+}
+
+/// Suffix describing a failed or dry-run action
+fn describe(outcome: &ActionOutcome) -> String {
+    match (&outcome.error, outcome.dry_run) {
+        (Some(e), _) => format!(" - failed: {}", e),
+        (None, true) => " (dry run)".to_string(),
+        (None, false) => String::new(),
+    }
+}
+
+/// The agent failed a step: back off, click Retry when due, or give up and
+/// cool down once the attempts in the window are spent
+fn retry(
+    app: &AppHandle,
+    ctx: &ActionContext,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    ui: &UIStateResult,
+) -> Decision {
+    let window_handle = instance.window_handle;
+    let policy = ctx.config.get().retry_for(Some(&instance.project_name));
+    let max = policy.max_attempts(settings);
+    let now = runtime::now_ms();
+
+    match ctx
+        .runtime
+        .with(window_handle, |rt| retry::next(&policy, settings, rt, now))
+    {
+        retry::Step::Wait {
+            remaining_ms,
+            attempt,
+        } => Decision::note(format!(
+            "Retry {}/{} in {}s",
+            attempt,
+            max,
+            remaining_ms.div_ceil(1000)
+        )),
+        retry::Step::Retry { attempt } => {
+            let outcome = actions::click(
+                ctx,
+                window_handle,
+                ElementKind::Retry,
+                ui.retry_button_x,
+                ui.retry_button_y,
+                Some(&instance.project_name),
+            );
+            ctx.runtime.with(window_handle, |rt| {
+                retry::record(&policy, rt, now);
+                rt.last_activity = now;
+            });
+            Decision::note(format!(
+                "Clicked Retry ({}/{}){}",
+                attempt,
+                max,
+                describe(&outcome)
+            ))
+        }
+        retry::Step::GiveUp { attempts } => {
+            let rt = ctx.runtime.get(window_handle).unwrap_or_default();
+            let (reason, next_step) = match policy.cooldown_minutes {
+                0 => (
+                    "Max retries reached".to_string(),
+                    "El proyecto queda deshabilitado.".to_string(),
+                ),
+                minutes => (
+                    format!("Max retries reached, cooling down {} min", minutes),
+                    format!("Se reanudará automáticamente en {} minutos.", minutes),
+                ),
+            };
+            if settings.notify_on_error {
+                let (title, message) = attention(instance, "Max retries reached");
+                send_notification(
+                    app,
+                    instance,
+                    settings,
+                    title,
+                    format!(
+                        "{} Intentos ({}): {}. {}",
+                        message,
+                        attempts,
+                        retry::describe_history(&rt),
+                        next_step
+                    ),
+                );
+            }
+            Decision::stop(
+                format!("Max retries ({}) reached", max),
+                InstanceState::Error,
+                reason,
+            )
+        }
+    }
+}
+
+/// Carry out the actions of rules that fired. The first pause, block or
+/// complete action decides the instance's new state; the rest still run.
+fn apply_firings(
+    app: &AppHandle,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    firings: Vec<RuleFiring>,
+) -> Option<Decision> {
+    let runtime = app.state::<Runtime>();
+    let name = &instance.project_name;
+    let mut decision = None;
+
+    for firing in firings {
+        notify::log(
+            settings,
+            "WARN",
+            &format!(
+                "[{}] Rule \"{}\" fired: {}",
+                name, firing.rule, firing.matched
+            ),
+        );
+        let complete = firing.actions.contains(&RuleAction::Complete);
+        let default_reason = format!("{}: {}", firing.rule, firing.matched);
+
+        for action in &firing.actions {
+            match action {
+                RuleAction::Pause => {
+                    decision.get_or_insert_with(|| {
+                        Decision::stop(
+                            format!("Rule \"{}\" paused the instance", firing.rule),
+                            InstanceState::Paused,
+                            default_reason.clone(),
+                        )
+                    });
+                }
+                RuleAction::Block { reason } => {
+                    let reason = reason.clone().unwrap_or_else(|| default_reason.clone());
+                    // Open blockers are carried into the next new chat
+                    runtime.with(instance.window_handle, |rt| {
+                        if !rt.blockers.contains(&default_reason) {
+                            rt.blockers.push(default_reason.clone());
+                        }
+                    });
+                    decision.get_or_insert_with(|| {
+                        Decision::stop(
+                            format!("Stop condition detected: {}", firing.rule),
+                            InstanceState::Blocked,
+                            reason,
+                        )
+                    });
+                }
+                RuleAction::Complete => {
+                    decision.get_or_insert_with(|| {
+                        Decision::stop(
+                            format!("Stop condition detected: {}", firing.rule),
+                            InstanceState::Complete,
+                            "Backlog completado",
+                        )
+                    });
+                }
+                RuleAction::Notify { title, message } => {
+                    let (default_title, default_message) = if complete {
+                        completion(app, instance)
+                    } else {
+                        attention(instance, &firing.rule)
+                    };
+                    send_notification(
+                        app,
+                        instance,
+                        settings,
+                        title.clone().unwrap_or(default_title),
+                        message.clone().unwrap_or(default_message),
+                    );
+                }
+                RuleAction::Reply { text } => {
+                    runtime.with(instance.window_handle, |rt| {
+                        rt.pending_reply = Some(text.clone())
+                    });
+                }
+                RuleAction::Hook { command } => {
+                    let dry_run = app
+                        .state::<ConfigStore>()
+                        .get()
+                        .dry_run_for(Some(name), settings.dry_run);
+                    rules::run_hook(settings, dry_run, command, name, &firing);
+                }
+            }
+        }
+    }
+    decision
+}
+
+/// Notification for a condition that needs a human
+fn attention(instance: &SupervisedInstance, condition: &str) -> (String, String) {
+    (
+        format!("⚠️ {} - Requiere Atención", instance.project_name),
+        format!(
+            "Condición detectada: {}. Requiere intervención manual.",
+            condition
+        ),
+    )
+}
+
+/// Notification for a backlog the agent reported as done
+fn completion(app: &AppHandle, instance: &SupervisedInstance) -> (String, String) {
+    let completed = app
+        .state::<Runtime>()
+        .get(instance.window_handle)
+        .and_then(|rt| rt.backlog)
+        .map(|b| b.completed_issues)
+        .unwrap_or(0);
+    (
+        format!("✅ {} - Backlog Completado", instance.project_name),
+        format!(
+            "El backlog ha sido completado exitosamente. Issues completados: {}",
+            completed
+        ),
+    )
+}
+
+fn send_notification(
+    app: &AppHandle,
+    instance: &SupervisedInstance,
+    settings: &Settings,
+    title: String,
+    message: String,
+) {
+    let dry_run = app
+        .state::<ConfigStore>()
+        .get()
+        .dry_run_for(Some(&instance.project_name), settings.dry_run);
+    notify::spawn_discord(settings, dry_run, title, message);
+}
+
+/// Log a decision and stream it to the frontend
+fn publish(app: &AppHandle, instance: &SupervisedInstance, decision: Decision) {
+    println!(
+        "[supervisor] [{}] {}",
+        instance.project_name, decision.message
+    );
+    if let Some(ref reason) = decision.stopped {
+        notify::log(
+            &app.state::<SettingsStore>().get(),
+            "WARN",
+            &format!("[{}] Disabled: {}", instance.project_name, reason),
+        );
+    }
+
+    let runtime = app.state::<Runtime>();
+    if let Some(to) = decision.status {
+        let reason = decision.stopped.as_deref().unwrap_or(&decision.message);
+        lifecycle::apply(&runtime, instance.window_handle, to, reason);
+    }
+    let rt = runtime.get(instance.window_handle).unwrap_or_default();
+    let _ = app.emit(
+        "supervisor-update",
+        SupervisorUpdate {
+            window_handle: instance.window_handle,
+            project_name: instance.project_name.clone(),
+            message: decision.message,
+            status: rt.state.state,
+            enabled: decision.stopped.is_none(),
+            resumed: decision.resumed,
+            block_reason: decision.stopped,
+            step_count: rt.step_count,
+            retry_count: rt.retry_count,
+            last_activity: rt.last_activity,
+            last_prompt_sent: rt.last_prompt_at,
+            last_response: rt.last_response,
+            backlog: rt.backlog,
+            ui_state: decision.ui_state,
+        },
+    );
+}
+
+/// Start (or re-target) the supervisor loop over the given instances
+#[tauri::command]
+pub fn start_supervisor(
+    app: AppHandle,
+    supervisor: tauri::State<'_, Supervisor>,
+    settings: tauri::State<'_, SettingsStore>,
+    instances: Vec<SupervisedInstance>,
+) -> SupervisorState {
+    if let Some(generation) = supervisor.start(instances) {
+        notify::log(&settings.get(), "INFO", "Supervisor started");
+        tauri::async_runtime::spawn(run_loop(app, generation));
+    }
+    supervisor.snapshot()
+}
+
+/// Stop the supervisor loop (an action already running finishes first)
+#[tauri::command]
+pub fn stop_supervisor(
+    supervisor: tauri::State<'_, Supervisor>,
+    settings: tauri::State<'_, SettingsStore>,
+    runtime: tauri::State<'_, Runtime>,
+) -> SupervisorState {
+    supervisor.stop();
+    // Nothing will act on the instances until the next start
+    for instance in supervisor.snapshot().instances {
+        let state = runtime
+            .get(instance.window_handle)
+            .map(|rt| rt.state.state)
+            .unwrap_or_default();
+        if matches!(
+            state,
+            InstanceState::Working | InstanceState::Stalled | InstanceState::OffHours
+        ) {
+            lifecycle::apply(
+                &runtime,
+                instance.window_handle,
+                InstanceState::Idle,
+                "Supervisor stopped",
+            );
+        }
+    }
+    notify::log(&settings.get(), "INFO", "Supervisor stopped");
+    supervisor.snapshot()
+}
+
+/// Add or update one supervised instance (e.g. toggled on the card)
+#[tauri::command]
+pub fn update_supervised_instance(
+    supervisor: tauri::State<'_, Supervisor>,
+    instance: SupervisedInstance,
+) {
+    supervisor.upsert(instance);
+}
+
+/// Whether the supervisor is running and what it drives
+#[tauri::command]
+pub fn get_supervisor_status(supervisor: tauri::State<'_, Supervisor>) -> SupervisorState {
+    supervisor.snapshot()
+}
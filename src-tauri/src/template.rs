@@ -0,0 +1,375 @@
+// Prompt templates
+// Prompts may reference instance and backlog state as `{{variable}}` and use
+// `{{#if variable}}…{{else}}…{{/if}}` (or `#unless`) blocks; a variable is
+// false when empty, "0" or "false". Every prompt is rendered before it's
+// written to the chat, so plain prompts go through unchanged: unknown
+// variables are left as written and `\{{` is a literal "{{".
+
+use serde::Serialize;
+use std::collections::BTreeMap;
+
+use crate::runtime::{InstanceRuntime, Runtime};
+
+/// Variables available to templates, by name
+pub type Vars = BTreeMap<&'static str, String>;
+
+/// Variables for an instance from its latest runtime state
+pub fn vars(rt: &InstanceRuntime, project_name: Option<&str>) -> Vars {
+    let backlog = rt.backlog.clone().unwrap_or_default();
+    let issue = |title: &str| match title {
+        "DONE" => String::new(),
+        title => title.to_string(),
+    };
+    let now = chrono::Local::now();
+
+    Vars::from([
+        (
+            "projectName",
+            project_name
+                .map(str::to_string)
+                .or_else(|| rt.project_name.clone())
+                .unwrap_or_default(),
+        ),
+        ("currentIssue", issue(&backlog.current_issue)),
+        ("nextIssueTitle", issue(&backlog.next_issue)),
+        (
+            "lastCompletedIssue",
+            backlog.recent_completed.last().cloned().unwrap_or_default(),
+        ),
+        ("completed", backlog.completed_issues.to_string()),
+        ("total", backlog.total_issues.to_string()),
+        (
+            "remaining",
+            (backlog.total_issues - backlog.completed_issues)
+                .max(0)
+                .to_string(),
+        ),
+        ("retryCount", rt.retry_count.to_string()),
+        ("stepCount", rt.step_count.to_string()),
+        (
+            "playbookStep",
+            rt.playbook
+                .as_ref()
+                .map(|p| p.step_name.clone())
+                .unwrap_or_default(),
+        ),
+        ("date", now.format("%Y-%m-%d").to_string()),
+        ("time", now.format("%H:%M").to_string()),
+    ])
+}
+
+#[derive(Debug)]
+enum Node<'a> {
+    Text(&'a str),
+    /// `raw` is the tag as written, kept when the variable doesn't exist
+    Var {
+        name: &'a str,
+        raw: &'a str,
+    },
+    If {
+        var: &'a str,
+        negate: bool,
+        then: Vec<Node<'a>>,
+        otherwise: Vec<Node<'a>>,
+        raw: &'a str,
+    },
+}
+
+/// What ended a block of nodes
+#[derive(Debug, PartialEq)]
+enum End<'a> {
+    Eof,
+    Else,
+    /// `{{/if}}` or `{{/unless}}`, by block name
+    Close(&'a str),
+}
+
+/// Parse nodes up to the end of the input or the `{{else}}`/`{{/if}}` closing the block
+fn parse<'a>(rest: &mut &'a str, nodes: &mut Vec<Node<'a>>) -> Result<End<'a>, String> {
+    loop {
+        let Some(open) = rest.find("{{") else {
+            if !rest.is_empty() {
+                nodes.push(Node::Text(rest));
+            }
+            *rest = "";
+            return Ok(End::Eof);
+        };
+        // `\{{` is a literal "{{"
+        if rest[..open].ends_with('\\') {
+            if open > 1 {
+                nodes.push(Node::Text(&rest[..open - 1]));
+            }
+            nodes.push(Node::Text("{{"));
+            *rest = &rest[open + 2..];
+            continue;
+        }
+        if open > 0 {
+            nodes.push(Node::Text(&rest[..open]));
+        }
+        let start = &rest[open..];
+        let after = &start[2..];
+        let close = after
+            .find("}}")
+            .ok_or_else(|| format!("Unclosed \"{{{{\" near \"{}\"", snippet(after)))?;
+        let tag = after[..close].trim();
+        *rest = &after[close + 2..];
+
+        match tag.split_once(char::is_whitespace) {
+            Some((kind @ ("#if" | "#unless"), var)) => {
+                let block = &kind[1..];
+                let mut then = Vec::new();
+                let mut otherwise = Vec::new();
+                let mut end = parse(rest, &mut then)?;
+                if end == End::Else {
+                    end = parse(rest, &mut otherwise)?;
+                }
+                match end {
+                    End::Close(name) if name == block => {}
+                    End::Close(name) => {
+                        return Err(format!(
+                            "{{{{/{}}}}} closes a {{{{{} {}}}}} block",
+                            name,
+                            kind,
+                            var.trim()
+                        ))
+                    }
+                    _ => {
+                        return Err(format!(
+                            "Missing {{{{/{}}}}} for {{{{{} {}}}}}",
+                            block,
+                            kind,
+                            var.trim()
+                        ))
+                    }
+                }
+                nodes.push(Node::If {
+                    var: var.trim(),
+                    negate: kind == "#unless",
+                    then,
+                    otherwise,
+                    raw: &start[..start.len() - rest.len()],
+                });
+            }
+            _ => match tag {
+                "else" => return Ok(End::Else),
+                "/if" => return Ok(End::Close("if")),
+                "/unless" => return Ok(End::Close("unless")),
+                "" => return Err("Empty {{}} in template".to_string()),
+                var if var.starts_with(['#', '/']) => {
+                    return Err(format!("Unknown block {{{{{}}}}}", var))
+                }
+                var => nodes.push(Node::Var {
+                    name: var,
+                    raw: &start[..close + 4],
+                }),
+            },
+        }
+    }
+}
+
+fn snippet(text: &str) -> String {
+    text.chars().take(20).collect()
+}
+
+fn truthy(value: &str) -> bool {
+    !value.is_empty() && value != "0" && value != "false"
+}
+
+/// Unknown variables are written as they appear in the template
+fn write(nodes: &[Node], vars: &Vars, out: &mut String, unknown: &mut Vec<String>) {
+    for node in nodes {
+        match node {
+            Node::Text(text) => out.push_str(text),
+            Node::Var { name, raw } => match vars.get(name) {
+                Some(value) => out.push_str(value),
+                None => {
+                    unknown.push(name.to_string());
+                    out.push_str(raw);
+                }
+            },
+            Node::If {
+                var,
+                negate,
+                then,
+                otherwise,
+                raw,
+            } => match vars.get(var) {
+                Some(value) => {
+                    let branch = if truthy(value) != *negate {
+                        then
+                    } else {
+                        otherwise
+                    };
+                    write(branch, vars, out, unknown);
+                }
+                None => {
+                    unknown.push(var.to_string());
+                    out.push_str(raw);
+                }
+            },
+        }
+    }
+}
+
+/// A rendered template and the variables it used that don't exist (left as written)
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct Rendered {
+    pub text: String,
+    pub unknown: Vec<String>,
+}
+
+/// Render a template; malformed blocks are an error
+pub fn render(template: &str, vars: &Vars) -> Result<Rendered, String> {
+    let mut rest = template;
+    let mut nodes = Vec::new();
+    match parse(&mut rest, &mut nodes)? {
+        End::Eof => {}
+        End::Else => return Err("{{else}} outside an {{#if}} block".to_string()),
+        End::Close(block) => {
+            return Err(format!("{{{{/{}}}}} without an {{{{#{}}}}}", block, block))
+        }
+    }
+
+    let mut rendered = Rendered {
+        text: String::new(),
+        unknown: Vec::new(),
+    };
+    write(&nodes, vars, &mut rendered.text, &mut rendered.unknown);
+    rendered.unknown.sort();
+    rendered.unknown.dedup();
+    Ok(rendered)
+}
+
+/// A template rendered against a live instance, with the values it saw
+#[derive(Debug, Clone, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PromptPreview {
+    #[serde(flatten)]
+    pub rendered: Rendered,
+    pub variables: Vars,
+}
+
+/// Render a prompt template against an instance's current state
+#[tauri::command]
+pub fn preview_prompt(
+    runtime: tauri::State<'_, Runtime>,
+    window_handle: i64,
+    template: String,
+    project_name: Option<String>,
+) -> Result<PromptPreview, String> {
+    let rt = runtime.get(window_handle).unwrap_or_default();
+    let variables = vars(&rt, project_name.as_deref());
+    Ok(PromptPreview {
+        rendered: render(&template, &variables)?,
+        variables,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn vars() -> Vars {
+        Vars::from([
+            ("projectName", "bob".to_string()),
+            ("currentIssue", "Fix login".to_string()),
+            ("completed", "3".to_string()),
+            ("retryCount", "0".to_string()),
+            ("flag", "false".to_string()),
+            ("empty", String::new()),
+        ])
+    }
+
+    fn text(template: &str) -> String {
+        render(template, &vars()).unwrap().text
+    }
+
+    #[test]
+    fn substitutes_variables() {
+        assert_eq!(
+            text("Continue {{projectName}}: {{ currentIssue }} ({{completed}})"),
+            "Continue bob: Fix login (3)"
+        );
+        assert_eq!(text("plain prompt"), "plain prompt");
+        assert_eq!(text(""), "");
+    }
+
+    #[test]
+    fn if_else_and_unless() {
+        assert_eq!(
+            text("{{#if currentIssue}}on {{currentIssue}}{{/if}}"),
+            "on Fix login"
+        );
+        assert_eq!(text("{{#if empty}}yes{{else}}no{{/if}}"), "no");
+        assert_eq!(
+            text("{{#unless retryCount}}first try{{/unless}}"),
+            "first try"
+        );
+        assert_eq!(
+            text("{{#unless completed}}none{{else}}some{{/unless}}"),
+            "some"
+        );
+    }
+
+    #[test]
+    fn nested_blocks() {
+        let template =
+            "{{#if completed}}A{{#unless flag}}B{{#if empty}}C{{else}}D{{/if}}{{/unless}}{{/if}}";
+        assert_eq!(text(template), "ABD");
+    }
+
+    #[test]
+    fn falsy_values() {
+        assert!(truthy("1"));
+        assert!(truthy("no"));
+        assert!(!truthy(""));
+        assert!(!truthy("0"));
+        assert!(!truthy("false"));
+    }
+
+    #[test]
+    fn unknown_variables_are_left_as_written() {
+        let rendered = render(
+            "Hi {{ user.name }} {{#if missing}}x{{/if}} {{user.name}}",
+            &vars(),
+        )
+        .unwrap();
+        assert_eq!(
+            rendered.text,
+            "Hi {{ user.name }} {{#if missing}}x{{/if}} {{user.name}}"
+        );
+        assert_eq!(rendered.unknown, vec!["missing", "user.name"]);
+    }
+
+    #[test]
+    fn escaped_braces_are_literal() {
+        assert_eq!(
+            text("\\{{projectName}} is {{projectName}}"),
+            "{{projectName}} is bob"
+        );
+        assert_eq!(text("\\{{#each items}}"), "{{#each items}}");
+    }
+
+    #[test]
+    fn malformed_templates_are_errors() {
+        let error = |template: &str| render(template, &vars()).unwrap_err();
+        assert_eq!(
+            error("a {{projectName"),
+            "Unclosed \"{{\" near \"projectName\""
+        );
+        assert_eq!(
+            error("{{#if completed}}x"),
+            "Missing {{/if}} for {{#if completed}}"
+        );
+        assert_eq!(error("x{{else}}y"), "{{else}} outside an {{#if}} block");
+        assert_eq!(error("x{{/if}}"), "{{/if}} without an {{#if}}");
+        assert_eq!(error("x{{/unless}}"), "{{/unless}} without an {{#unless}}");
+        assert_eq!(error("{{ }}"), "Empty {{}} in template");
+        assert_eq!(error("{{#each items}}"), "Unknown block {{#each items}}");
+        assert_eq!(
+            error("{{#unless flag}}x{{/if}}"),
+            "{{/if}} closes a {{#unless flag}} block"
+        );
+    }
+}
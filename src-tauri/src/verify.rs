@@ -0,0 +1,154 @@
+// Post-action verification
+// After an action, re-detect until the expected UI transition shows up or
+// the timeout passes; unconfirmed actions are retried or escalated
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{ConfigStore, InstanceConfig};
+use crate::detect::{DetectionConfig, ElementKind, UIStateResult};
+use crate::layout::LayoutProfile;
+
+/// Distance (px) within which a detected element counts as the one clicked
+#[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+const SAME_ELEMENT_RADIUS: i32 = 40;
+
+/// Verification policy, global or per instance
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct VerifyPolicy {
+    pub enabled: bool,
+    /// How long to wait for the transition
+    pub timeout_ms: u64,
+    /// Delay between re-detections
+    pub interval_ms: u64,
+    /// Extra attempts when a click or shortcut isn't confirmed
+    pub retries: u32,
+    /// Also retry unconfirmed prompt sends (off by default: a slow UI
+    /// would otherwise get the prompt twice)
+    pub retry_sends: bool,
+    /// Log and notify when an action stays unconfirmed after all attempts
+    pub escalate: bool,
+}
+
+impl Default for VerifyPolicy {
+    fn default() -> Self {
+        VerifyPolicy {
+            enabled: true,
+            timeout_ms: 3000,
+            interval_ms: 300,
+            retries: 1,
+            retry_sends: false,
+            escalate: true,
+        }
+    }
+}
+
+/// UI transition an action is expected to cause
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub enum Expectation {
+    /// The clicked button is no longer there (another kind of element
+    /// showing up nearby doesn't count)
+    ElementGone { kind: ElementKind, x: i32, y: i32 },
+    /// No Accept dialog (Alt+Enter accepted it)
+    DialogGone,
+    /// The chat send/stop button is visible (scrolled to the bottom)
+    ChatVisible,
+    /// The agent started working (stop button red)
+    Working,
+}
+
+impl Expectation {
+    #[cfg_attr(not(target_os = "windows"), allow(dead_code))]
+    pub fn is_met(&self, state: &UIStateResult) -> bool {
+        match *self {
+            Expectation::ElementGone { kind, x, y } => !state.elements.iter().any(|e| {
+                e.kind == kind
+                    && (e.x - x).abs() <= SAME_ELEMENT_RADIUS
+                    && (e.y - y).abs() <= SAME_ELEMENT_RADIUS
+            }),
+            Expectation::DialogGone => !state.has_accept_button || state.is_bottom_button,
+            Expectation::ChatVisible => {
+                state.chat_button_color == "gray" || state.chat_button_color == "red"
+            }
+            Expectation::Working => state.is_paused || state.chat_button_color == "red",
+        }
+    }
+
+    /// Whether repeating the action is safe if it wasn't confirmed
+    pub fn can_retry(&self, policy: &VerifyPolicy) -> bool {
+        match self {
+            Expectation::Working => policy.retry_sends,
+            _ => true,
+        }
+    }
+}
+
+/// Re-detect (without refocusing or scrolling) until the expectation holds
+#[cfg(target_os = "windows")]
+pub fn confirm(
+    window_handle: i64,
+    layout: &LayoutProfile,
+    detection: &DetectionConfig,
+    expectation: Expectation,
+    policy: &VerifyPolicy,
+) -> bool {
+    use crate::detect::{check_reliability, scan, ScreenPixels};
+
+    let deadline = std::time::Instant::now() + std::time::Duration::from_millis(policy.timeout_ms);
+    loop {
+        std::thread::sleep(std::time::Duration::from_millis(policy.interval_ms));
+
+        if let Some(rect) = crate::layout::window_rect(window_handle) {
+            let pixels = ScreenPixels::new();
+            if check_reliability(&pixels, &rect).is_none() {
+                let state = scan(&pixels, &rect, layout, detection);
+                if expectation.is_met(&state) {
+                    return true;
+                }
+            }
+        }
+
+        if std::time::Instant::now() >= deadline {
+            return false;
+        }
+    }
+}
+
+#[cfg(not(target_os = "windows"))]
+pub fn confirm(
+    _window_handle: i64,
+    _layout: &LayoutProfile,
+    _detection: &DetectionConfig,
+    _expectation: Expectation,
+    _policy: &VerifyPolicy,
+) -> bool {
+    false
+}
+
+/// Get the verification policy for an instance (or the global one)
+#[tauri::command]
+pub fn get_verify_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+) -> VerifyPolicy {
+    config.get().verify_for(project_name.as_deref())
+}
+
+/// Set the global verification policy, or an instance override when project_name is given
+#[tauri::command]
+pub fn set_verify_policy(
+    config: tauri::State<'_, ConfigStore>,
+    project_name: Option<String>,
+    policy: VerifyPolicy,
+) -> Result<(), String> {
+    config.update(|c| match project_name {
+        Some(name) => {
+            c.instances
+                .entry(name)
+                .or_insert_with(InstanceConfig::default)
+                .verify = Some(policy);
+        }
+        None => c.verify = policy,
+    })
+}
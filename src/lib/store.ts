@@ -1,8 +1,9 @@
 // Svelte stores for Antigravity Monitor state management
 
 import { writable, get } from 'svelte/store';
-import type { Instance, Settings, BacklogConfig } from './types';
+import type { Instance, StateHistory, Rule, RuleFiring, Settings, BacklogConfig, LayoutProfile, KeybindingProfile, CalibrationTarget, StopCondition, ActionOutcome, RetryPolicy, Schedule, PromptPreview, Playbook, PlaybookProgress, BudgetPolicy, BudgetUsage } from './types';
 import { invoke } from '@tauri-apps/api/core';
+import { listen, type UnlistenFn } from '@tauri-apps/api/event';
 
 // Default settings
 const defaultSettings: Settings = {
@@ -24,7 +25,19 @@ const defaultSettings: Settings = {
         'ESCALAR',
         '✅ BACKLOG COMPLETADO'
     ],
-    inactivityTimeoutMinutes: 20,  // Stop project if no prompt sent in 20 minutes
+    inactivityTimeoutMinutes: 20,  // Time out a project with no activity for 20 minutes
+    inactivityRecoveryPrompt: '',
+    // Stall detection
+    stallTimeoutMinutes: 15,
+    notifyOnStall: true,
+    // Don't take focus until the user has been idle this long
+    userIdleSeconds: 5,
+    actionTimeoutSeconds: 30,
+    dryRun: false,
+    // Context reset
+    newChatAfterSteps: 0,
+    newChatOnDegradation: true,
+    degradationPhrases: ['context window', 'conversation is too long', 'context limit'],
     // Logging settings
     loggingEnabled: true,
     logFilePath: ''  // Empty = use default location (app data dir)
@@ -41,9 +54,18 @@ function loadSettings(): Settings {
     return defaultSettings;
 }
 
+// Mirror settings into the backend (used by backend monitors like stall detection)
+function syncSettings(value: Settings) {
+    invoke('sync_settings', { settings: value }).catch(error =>
+        console.error('Failed to sync settings to backend:', error)
+    );
+}
+
 // Settings store with persistence
 function createSettingsStore() {
-    const { subscribe, set, update } = writable<Settings>(loadSettings());
+    const initial = loadSettings();
+    const { subscribe, set, update } = writable<Settings>(initial);
+    syncSettings(initial);
 
     return {
         subscribe,
@@ -52,6 +74,7 @@ function createSettingsStore() {
                 localStorage.setItem('bob-settings', JSON.stringify(value));
             }
             set(value);
+            syncSettings(value);
         },
         update
     };
@@ -193,7 +216,8 @@ export async function updateInstanceBacklogs(): Promise<void> {
             }>('read_backlog', {
                 projectPath: instance.projectPath,
                 backlogPath: config?.path || null,
-                mode: config?.mode || null
+                mode: config?.mode || null,
+                windowHandle: instance.windowHandle
             });
 
             console.log(`[${instance.projectName}] Backlog result:`, backlog);
@@ -265,14 +289,34 @@ export async function refreshInstances(): Promise<void> {
                 windowHandle: instance.windowHandle
             });
 
-            // Don't overwrite fields managed by polling (stepCount, retryCount, backlog fields)
-            // Only update status and lastActivity from get_instance_status
+            // The backend owns the counters; backlog fields only once a read succeeded
             instances.update(list =>
                 list.map(i => i.id === instance.id ? {
                     ...i,
-                    status: status.status,
-                    lastActivity: status.lastActivity
-                    // NOT including: stepCount, retryCount, totalIssues, currentIssue, issuesCompleted
+                    // Waiting for the user to go idle overrides the lifecycle state
+                    status: status.waitingForIdle ? 'waiting' : status.status,
+                    stateReason: status.stateReason,
+                    lastActivity: status.lastActivity || i.lastActivity,
+                    lastVisualChange: status.lastVisualChange || i.lastVisualChange,
+                    stepCount: status.stepCount,
+                    retryCount: status.retryCount,
+                    lastPromptSent: status.lastPromptSent || i.lastPromptSent,
+                    lastRuleFiring: status.lastRuleFiring,
+                    nextRetryAt: status.nextRetryAt,
+                    retryCooldownUntil: status.retryCooldownUntil,
+                    nextRunAt: status.nextRunAt,
+                    playbook: status.playbook,
+                    budget: status.budget,
+                    budgetPolicy: status.budgetPolicy,
+                    ...(status.totalIssues > 0 ? {
+                        totalIssues: status.totalIssues,
+                        currentIssue: status.currentIssue,
+                        issuesCompleted: status.issuesCompleted,
+                        currentIssueTitle: status.currentIssueTitle
+                    } : {}),
+                    queueDepth: status.queueDepth,
+                    currentAction: status.currentAction,
+                    dryRun: status.dryRun
                 } : i)
             );
         } catch (error) {
@@ -308,12 +352,30 @@ interface ScanResult {
 }
 
 interface InstanceStatus {
-    status: 'idle' | 'working' | 'error' | 'complete';
+    status: 'idle' | 'working' | 'stalled' | 'error' | 'complete' | 'blocked' | 'paused' | 'timedOut' | 'offHours' | 'disabled';
+    stateReason: string | null;
     currentIssue: number;
     totalIssues: number;
     retryCount: number;
     lastActivity: number;
     stepCount: number;
+    issuesCompleted: number;
+    currentIssueTitle: string | null;
+    lastPromptSent: number;
+    lastDetection: UIStateResult | null;
+    lastDetectionAt: number;
+    lastRuleFiring: RuleFiring | null;
+    nextRetryAt: number;
+    retryCooldownUntil: number;
+    nextRunAt: number;
+    playbook: PlaybookProgress | null;
+    budget: BudgetUsage;
+    budgetPolicy: BudgetPolicy;
+    lastVisualChange: number;
+    waitingForIdle: boolean;
+    queueDepth: number;
+    currentAction: string | null;
+    dryRun: boolean;
 }
 
 // UI Automation types
@@ -330,13 +392,25 @@ interface UIStateResult {
     retryButtonX: number;
     retryButtonY: number;
     isBottomButton: boolean;  // True = Accept all (needs click), False = dialog (use Alt+Enter)
+    elements: DetectedElement[];  // Every element found, with confidence
+    stable: boolean;  // True once enough consecutive frames agreed
+    agreeingFrames: number;
     error?: string;
 }
 
-// Detect UI state for a window
-export async function detectUIState(windowHandle: number): Promise<UIStateResult | null> {
+interface DetectedElement {
+    kind: 'accept' | 'pause' | 'chatRed' | 'retry' | 'enter';
+    x: number;
+    y: number;
+    confidence: number;  // matched / sampled pixels (0-1)
+    matchedPixels: number;
+    sampledPixels: number;
+}
+
+// Detect UI state for a window (projectName selects the instance layout profile)
+export async function detectUIState(windowHandle: number, projectName?: string): Promise<UIStateResult | null> {
     try {
-        const result = await invoke<UIStateResult>('detect_ui_state', { windowHandle });
+        const result = await invoke<UIStateResult>('detect_ui_state', { windowHandle, projectName });
         return result;
     } catch (error) {
         console.error('Failed to detect UI state:', error);
@@ -344,15 +418,36 @@ export async function detectUIState(windowHandle: number): Promise<UIStateResult
     }
 }
 
+// Capture an annotated PNG + JSON report of a detection pass into the diagnostics folder
+export async function debugDetect(windowHandle: number, projectName?: string): Promise<{ imagePath: string; reportPath: string } | null> {
+    try {
+        const result = await invoke<{ imagePath: string; reportPath: string }>('debug_detect', { windowHandle, projectName });
+        await log.info(`[${projectName || windowHandle}] Debug capture saved: ${result.imagePath}`);
+        return result;
+    } catch (error) {
+        console.error('Failed to capture detection debug:', error);
+        return null;
+    }
+}
+
+// Log why an action failed and reduce the outcome to success/failure
+function actionSucceeded(outcome: ActionOutcome): boolean {
+    if (!outcome.success) {
+        console.warn(`[${outcome.action}] Failed at stage ${outcome.stage} after ${outcome.elapsedMs}ms (focus: ${outcome.focusObtained}): ${outcome.error || 'unknown error'}`);
+    }
+    return outcome.success;
+}
+
 // Click the accept/enter button (legacy - uses mouse click)
-export async function clickAcceptButton(windowHandle: number, x: number, y: number): Promise<boolean> {
+export async function clickAcceptButton(windowHandle: number, x: number, y: number, projectName?: string): Promise<boolean> {
     try {
-        const result = await invoke<boolean>('click_button', {
+        const result = await invoke<ActionOutcome>('click_button', {
             windowHandle,
             screenX: x,
-            screenY: y
+            screenY: y,
+            projectName
         });
-        return result;
+        return actionSucceeded(result);
     } catch (error) {
         console.error('Failed to click accept:', error);
         return false;
@@ -360,10 +455,10 @@ export async function clickAcceptButton(windowHandle: number, x: number, y: numb
 }
 
 // Accept dialog using Alt+Enter keyboard shortcut (more reliable)
-export async function acceptDialog(windowHandle: number): Promise<boolean> {
+export async function acceptDialog(windowHandle: number, projectName?: string): Promise<boolean> {
     try {
-        const result = await invoke<boolean>('accept_dialog', { windowHandle });
-        return result;
+        const result = await invoke<ActionOutcome>('accept_dialog', { windowHandle, projectName });
+        return actionSucceeded(result);
     } catch (error) {
         console.error('Failed to accept dialog:', error);
         return false;
@@ -371,10 +466,10 @@ export async function acceptDialog(windowHandle: number): Promise<boolean> {
 }
 
 // Scroll chat to bottom using Ctrl+End
-export async function scrollToBottom(windowHandle: number): Promise<boolean> {
+export async function scrollToBottom(windowHandle: number, projectName?: string): Promise<boolean> {
     try {
-        const result = await invoke<boolean>('scroll_to_bottom', { windowHandle });
-        return result;
+        const result = await invoke<ActionOutcome>('scroll_to_bottom', { windowHandle, projectName });
+        return actionSucceeded(result);
     } catch (error) {
         console.error('Failed to scroll to bottom:', error);
         return false;
@@ -382,8 +477,8 @@ export async function scrollToBottom(windowHandle: number): Promise<boolean> {
 }
 
 // Click the retry button
-export async function clickRetryButton(windowHandle: number, x: number, y: number): Promise<boolean> {
-    return clickAcceptButton(windowHandle, x, y);
+export async function clickRetryButton(windowHandle: number, x: number, y: number, projectName?: string): Promise<boolean> {
+    return clickAcceptButton(windowHandle, x, y, projectName);
 }
 
 // Backlog reading result interface
@@ -391,17 +486,20 @@ interface BacklogResult {
     totalIssues: number;
     completedIssues: number;
     currentIssue: string;
+    nextIssue?: string;  // Incomplete issue after the current one
+    recentCompleted?: string[];
     backlogPath: string;
     error?: string;
 }
 
 // Read backlog from project path
-export async function readBacklog(projectPath: string, config?: BacklogConfig): Promise<BacklogResult | null> {
+export async function readBacklog(projectPath: string, config?: BacklogConfig, windowHandle?: number): Promise<BacklogResult | null> {
     try {
         const result = await invoke<BacklogResult>('read_backlog', {
             projectPath,
             backlogPath: config?.path || null,
-            mode: config?.mode || null
+            mode: config?.mode || null,
+            windowHandle: windowHandle ?? null
         });
         return result;
     } catch (error) {
@@ -411,150 +509,151 @@ export async function readBacklog(projectPath: string, config?: BacklogConfig):
 }
 
 // Write to chat and submit
-export async function writeToChat(windowHandle: number, prompt: string): Promise<boolean> {
+export async function writeToChat(windowHandle: number, prompt: string, projectName?: string): Promise<boolean> {
     try {
-        const result = await invoke<boolean>('write_to_chat', {
+        const result = await invoke<ActionOutcome>('write_to_chat', {
             windowHandle,
-            prompt
+            prompt,
+            projectName
         });
-        return result;
+        return actionSucceeded(result);
     } catch (error) {
         console.error('Failed to write to chat:', error);
         return false;
     }
 }
 
-// Check UI state and perform auto-actions for enabled instances
-export async function checkAndActOnInstance(instanceId: string, testMode: boolean = false): Promise<string> {
-    const currentInstances = get(instances);
-    const instance = currentInstances.find(i => i.id === instanceId);
-
-    if (!instance) return 'Instance not found';
-    if (!instance.enabled && !testMode) return 'Instance disabled';
-
-    const uiState = await detectUIState(instance.windowHandle);
-    if (!uiState) return 'Failed to detect UI state';
-    if (uiState.error) return `Error: ${uiState.error}`;
-
-    const currentSettings = get(settings);
-
-    // Update instance UI state
-    instances.update(list =>
-        list.map(i => i.id === instanceId
-            ? { ...i, uiState: uiState, lastActivity: Date.now() }
-            : i
-        )
-    );
-
-    // Handle error state (Retry button detected)
-    if (uiState.hasRetryButton) {
-        const newRetryCount = instance.retryCount + 1;
+// Open a fresh chat seeded with a carry-over summary (backlog position, blockers)
+// Render a prompt template against an instance's current state
+export async function previewPrompt(instance: Instance, template: string): Promise<PromptPreview> {
+    return await invoke<PromptPreview>('preview_prompt', {
+        windowHandle: instance.windowHandle,
+        template,
+        projectName: instance.projectName
+    });
+}
 
-        if (newRetryCount >= instance.maxRetries) {
-            // Max retries reached - disable and notify
-            instances.update(list =>
-                list.map(i => i.id === instanceId
-                    ? { ...i, retryCount: newRetryCount, status: 'error', enabled: false }
-                    : i
-                )
-            );
+export async function newChat(instance: Instance, reason?: string): Promise<boolean> {
+    try {
+        const result = await invoke<ActionOutcome>('new_chat', {
+            windowHandle: instance.windowHandle,
+            projectName: instance.projectName,
+            reason: reason || null,
+            blockers: instance.blockReason ? [instance.blockReason] : null
+        });
+        return actionSucceeded(result);
+    } catch (error) {
+        console.error('Failed to open new chat:', error);
+        return false;
+    }
+}
 
-            // Send Discord notification
-            if (currentSettings.discordWebhook && currentSettings.notifyOnError) {
-                try {
-                    await invoke('notify_discord', {
-                        webhookUrl: currentSettings.discordWebhook,
-                        title: `❌ Error en ${instance.projectName}`,
-                        message: `Se alcanzó el máximo de reintentos (${instance.maxRetries}). Requiere atención manual.`
-                    });
-                } catch (e) {
-                    console.error('Failed to send Discord notification:', e);
-                }
-            }
-            return `Max retries reached (${newRetryCount}/${instance.maxRetries})`;
-        } else {
-            // Click retry and increment counter
-            await clickRetryButton(instance.windowHandle, uiState.retryButtonX, uiState.retryButtonY);
-            instances.update(list =>
-                list.map(i => i.id === instanceId
-                    ? { ...i, retryCount: newRetryCount, status: 'working', lastActivity: Date.now() }
-                    : i
-                )
-            );
-            return `Clicked Retry (attempt ${newRetryCount}/${instance.maxRetries})`;
-        }
+// Read the agent's last response and check it against the stop conditions
+export async function checkStopConditions(windowHandle: number, projectName?: string): Promise<StopCondition | null> {
+    try {
+        return await invoke<StopCondition>('check_stop_conditions', {
+            windowHandle,
+            projectName
+        });
+    } catch (error) {
+        console.error('Failed to check stop conditions:', error);
+        return null;
     }
+}
 
-    // Handle accept button - click it
-    if (uiState.hasAcceptButton) {
-        await clickAcceptButton(instance.windowHandle, uiState.acceptButtonX, uiState.acceptButtonY);
-        instances.update(list =>
-            list.map(i => i.id === instanceId
-                ? { ...i, status: 'working', lastActivity: Date.now() }
-                : i
-            )
-        );
-        return 'Clicked Accept button';
+// Read the agent's last response from the chat
+export async function readLastResponse(windowHandle: number, projectName?: string): Promise<string | null> {
+    try {
+        return await invoke<string>('read_last_response', {
+            windowHandle,
+            projectName
+        });
+    } catch (error) {
+        console.error('Failed to read last response:', error);
+        return null;
     }
+}
 
-    // Handle enter/ready state - chat is available, send prompt
-    if (uiState.hasEnterButton) {
-        const prompt = testMode ? 'Test' : (instance.customPrompt || currentSettings.defaultPrompt);
-        await writeToChat(instance.windowHandle, prompt);
-
-        // Update status
-        instances.update(list =>
-            list.map(i => i.id === instanceId
-                ? { ...i, retryCount: 0, status: 'working', lastActivity: Date.now(), stepCount: i.stepCount + 1 }
-                : i
-            )
-        );
-        return `Sent prompt: "${prompt}"`;
+// List built-in and user layout profiles
+export async function getLayoutProfiles(): Promise<LayoutProfile[]> {
+    try {
+        return await invoke<LayoutProfile[]>('get_layout_profiles');
+    } catch (error) {
+        console.error('Failed to load layout profiles:', error);
+        return [];
     }
+}
 
-    return 'No action needed - Antigravity is working';
+// Assign a layout profile to an instance (keyed by project name)
+export async function setInstanceLayout(projectName: string, profileName: string): Promise<void> {
+    await invoke('set_instance_layout', { projectName, profileName });
 }
 
-// UI Polling state
-let pollingInterval: ReturnType<typeof setInterval> | null = null;
-let pollingActive = false;
+// List built-in and user keybinding profiles
+export async function getKeybindingProfiles(): Promise<KeybindingProfile[]> {
+    try {
+        return await invoke<KeybindingProfile[]>('get_keybinding_profiles');
+    } catch (error) {
+        console.error('Failed to load keybinding profiles:', error);
+        return [];
+    }
+}
 
-// Start automatic UI polling for all enabled instances
-export function startUIPolling(intervalMs: number = 5000): void {
-    if (pollingActive) return;
+// Create or replace a user keybinding profile
+export async function saveKeybindingProfile(profile: KeybindingProfile): Promise<void> {
+    await invoke('save_keybinding_profile', { profile });
+}
 
-    pollingActive = true;
-    console.log(`Starting UI polling every ${intervalMs}ms`);
+// Assign a keybinding profile to an instance (keyed by project name)
+export async function setInstanceKeybindings(projectName: string, profileName: string): Promise<void> {
+    await invoke('set_instance_keybindings', { projectName, profileName });
+}
 
-    pollingInterval = setInterval(async () => {
-        const currentInstances = get(instances);
+// Override dry-run for one instance (null = follow the global setting)
+export async function setInstanceDryRun(projectName: string, dryRun: boolean | null): Promise<void> {
+    await invoke('set_instance_dry_run', { projectName, dryRun });
+}
 
-        for (const instance of currentInstances) {
-            if (!instance.enabled) continue;
+// Retry policy for an instance, or the global one when projectName is omitted
+export async function getRetryPolicy(projectName?: string): Promise<RetryPolicy | null> {
+    try {
+        return await invoke<RetryPolicy>('get_retry_policy', { projectName: projectName ?? null });
+    } catch (error) {
+        console.error('Failed to load retry policy:', error);
+        return null;
+    }
+}
 
-            try {
-                const result = await checkAndActOnInstance(instance.id, false);
-                console.log(`[${instance.projectName}] ${result}`);
-            } catch (error) {
-                console.error(`[${instance.projectName}] Error:`, error);
-            }
-        }
-    }, intervalMs);
+// Set the global retry policy, or an instance override when projectName is given
+export async function setRetryPolicy(policy: RetryPolicy, projectName?: string): Promise<void> {
+    await invoke('set_retry_policy', { projectName: projectName ?? null, policy });
 }
 
-// Stop UI polling
-export function stopUIPolling(): void {
-    if (pollingInterval) {
-        clearInterval(pollingInterval);
-        pollingInterval = null;
+// Global schedule, or an instance's own when projectName is given
+export async function getSchedule(projectName?: string): Promise<Schedule> {
+    try {
+        return await invoke<Schedule>('get_schedule', { projectName: projectName ?? null });
+    } catch (error) {
+        console.error('Failed to load schedule:', error);
+        return { runWindows: [], quietHours: [] };
     }
-    pollingActive = false;
-    console.log('UI polling stopped');
 }
 
-// Check if polling is active
-export function isPollingActive(): boolean {
-    return pollingActive;
+// Set the global schedule, or an instance's own when projectName is given
+export async function setSchedule(schedule: Schedule, projectName?: string): Promise<void> {
+    await invoke('set_schedule', { projectName: projectName ?? null, schedule });
+}
+
+// Record the cursor position as the chat input or send button after a delay
+export async function calibrateLayout(instance: Instance, target: CalibrationTarget, delayMs: number = 3000): Promise<LayoutProfile> {
+    const result = await invoke<{ profile: LayoutProfile }>('calibrate_layout', {
+        windowHandle: instance.windowHandle,
+        projectName: instance.projectName,
+        target,
+        delayMs
+    });
+    await log.info(`[${instance.projectName}] Calibrated ${target} in layout '${result.profile.name}'`);
+    return result.profile;
 }
 
 // Manual test function - directly send "Test" to chat
@@ -565,7 +664,7 @@ export async function testInstance(instanceId: string): Promise<string> {
     if (!instance) return 'Instance not found';
 
     try {
-        const success = await writeToChat(instance.windowHandle, 'Test');
+        const success = await writeToChat(instance.windowHandle, 'Test', instance.projectName);
         if (success) {
             instances.update(list =>
                 list.map(i => i.id === instanceId
@@ -605,7 +704,7 @@ export async function sendAutoPrompt(instanceId: string): Promise<string> {
     const prompt = instance.customPrompt || currentSettings.autoPrompt;
 
     try {
-        const success = await writeToChat(instance.windowHandle, prompt);
+        const success = await writeToChat(instance.windowHandle, prompt, instance.projectName);
         if (success) {
             instances.update(list =>
                 list.map(i => i.id === instanceId
@@ -627,399 +726,235 @@ export async function sendAutoPrompt(instanceId: string): Promise<string> {
     }
 }
 
-// Notify Discord about a stop condition or completion
-async function notifyStopCondition(instance: Instance, condition: string): Promise<void> {
-    const currentSettings = get(settings);
-    if (!currentSettings.discordWebhook) return;
-
-    const isComplete = condition === '✅ BACKLOG COMPLETADO';
-    const shouldNotify = isComplete ? currentSettings.notifyOnComplete : currentSettings.notifyOnError;
-
-    if (!shouldNotify) return;
-
-    try {
-        const title = isComplete
-            ? `✅ ${instance.projectName} - Backlog Completado`
-            : `⚠️ ${instance.projectName} - Requiere Atención`;
-
-        const message = isComplete
-            ? `El backlog ha sido completado exitosamente. Issues completados: ${instance.issuesCompleted || 0}`
-            : `Condición detectada: ${condition}. Requiere intervención manual.`;
-
-        await invoke('notify_discord', {
-            webhookUrl: currentSettings.discordWebhook,
-            title,
-            message
-        });
-    } catch (e) {
-        console.error('Failed to send Discord notification:', e);
-    }
+// Instance handed to the backend supervisor (same shape as SupervisedInstance in supervisor.rs)
+interface SupervisedInstance {
+    windowHandle: number;
+    projectName: string;
+    projectPath: string;
+    customPrompt: string | null;
+    backlogPath: string | null;
+    backlogMode: string | null;
+    enabled: boolean;
 }
 
-// Enhanced UI polling with stop condition detection - using recursive setTimeout to prevent overlap
-let pollingTimeout: ReturnType<typeof setTimeout> | null = null;
-
-async function pollOnce(): Promise<void> {
-    const currentInstances = get(instances);
-    const currentSettings = get(settings);
-
-    for (const instance of currentInstances) {
-        if (!pollingActive) break; // Check if stopped
-        if (!instance.enabled || instance.isBlocked) continue;
-
-        // ========== INACTIVITY TIMEOUT CHECK ==========
-        // If no prompt sent in X minutes, stop project and notify Discord
-        const inactivityMs = currentSettings.inactivityTimeoutMinutes * 60 * 1000;
-        const lastPrompt = instance.lastPromptSent || 0;
-        const timeSinceLastPrompt = Date.now() - lastPrompt;
-
-        if (lastPrompt > 0 && timeSinceLastPrompt > inactivityMs) {
-            const minutesInactive = Math.round(timeSinceLastPrompt / 60000);
-            console.log(`[${instance.projectName}] ⏰ Inactivity timeout: ${minutesInactive} minutes since last prompt`);
-
-            // Disable the instance
-            instances.update(list =>
-                list.map(i => i.id === instance.id
-                    ? {
-                        ...i,
-                        enabled: false,
-                        status: 'error' as const,
-                        isBlocked: true,
-                        blockReason: `Inactivity timeout: ${minutesInactive} min`
-                    }
-                    : i
-                )
-            );
-
-            // Send Discord notification
-            if (currentSettings.discordWebhook && currentSettings.notifyOnError) {
-                try {
-                    await invoke('notify_discord', {
-                        webhookUrl: currentSettings.discordWebhook,
-                        title: `⏰ ${instance.projectName} Detenido por Inactividad`,
-                        message: `No se ha enviado ningún prompt en ${minutesInactive} minutos. El proyecto ha sido deshabilitado automáticamente.`
-                    });
-                    console.log(`[${instance.projectName}] Discord notification sent for inactivity`);
-                } catch (e) {
-                    console.error(`[${instance.projectName}] Failed to send inactivity notification:`, e);
-                }
-            }
-
-            continue; // Skip further processing for this instance
-        }
-
-        // Update backlog for this instance on every poll (with timeout)
-        try {
-            const backlogTimeout = new Promise<null>((_, reject) =>
-                setTimeout(() => reject(new Error('Backlog timeout')), 20000)
-            );
-
-            const config = instance.backlogConfig;
-            const backlogPromise = invoke<{
-                totalIssues: number;
-                completedIssues: number;
-                currentIssue: string;
-                error?: string;
-            }>('read_backlog', {
-                projectPath: instance.projectPath,
-                backlogPath: config?.path || null,
-                mode: config?.mode || null
-            });
-
-            const backlog = await Promise.race([backlogPromise, backlogTimeout]) as {
-                totalIssues: number;
-                completedIssues: number;
-                currentIssue: string;
-                error?: string;
-            } | null;
-
-            if (backlog && !backlog.error) {
-                instances.update(list =>
-                    list.map(i => i.id === instance.id
-                        ? {
-                            ...i,
-                            totalIssues: backlog.totalIssues,
-                            currentIssue: backlog.completedIssues,
-                            issuesCompleted: backlog.completedIssues
-                        }
-                        : i
-                    )
-                );
-
-                // ========== CHECK FOR PROJECT COMPLETION ==========
-                // If all issues are completed, disable and notify
-                if (backlog.totalIssues > 0 && backlog.completedIssues >= backlog.totalIssues) {
-                    console.log(`[${instance.projectName}] 🎉 All issues completed (${backlog.completedIssues}/${backlog.totalIssues})`);
-
-                    // Update instance to disabled and completed
-                    instances.update(list =>
-                        list.map(i => i.id === instance.id
-                            ? {
-                                ...i,
-                                enabled: false,
-                                status: 'complete' as const,
-                                isBlocked: true,
-                                blockReason: 'All issues completed'
-                            }
-                            : i
-                        )
-                    );
-
-                    // Send Discord notification
-                    if (currentSettings.discordWebhook && currentSettings.notifyOnComplete) {
-                        try {
-                            await invoke('notify_discord', {
-                                webhookUrl: currentSettings.discordWebhook,
-                                title: `🎉 ${instance.projectName} Completado!`,
-                                message: `Todos los issues han sido completados (${backlog.completedIssues}/${backlog.totalIssues}). El proyecto ha sido deshabilitado automáticamente.`
-                            });
-                            console.log(`[${instance.projectName}] Discord notification sent for completion`);
-                        } catch (e) {
-                            console.error(`[${instance.projectName}] Failed to send completion notification:`, e);
-                        }
-                    }
-
-                    continue; // Skip further processing for this instance
-                }
-            }
-        } catch (e) {
-            // Ignore backlog read errors, continue with detection
-        }
-
-        try {
-            // Add timeout protection - max 45 seconds per instance
-            const timeoutPromise = new Promise<null>((_, reject) =>
-                setTimeout(() => reject(new Error('Timeout')), 45000)
-            );
-
-            const detectPromise = detectUIState(instance.windowHandle);
-            const uiState = await Promise.race([detectPromise, timeoutPromise]);
-
-            console.log(`[${instance.projectName}] UI State received:`, JSON.stringify(uiState));
-
-            if (!uiState || !pollingActive) {
-                console.log(`[${instance.projectName}] Skipping - uiState null or polling stopped`);
-                continue;
-            }
+// One supervisor decision, streamed as the 'supervisor-update' event
+interface SupervisorUpdate {
+    windowHandle: number;
+    projectName: string;
+    message: string;
+    status: Instance['status'];
+    enabled: boolean;
+    resumed: boolean;  // Back in the loop after a retry cooldown
+    blockReason: string | null;
+    stepCount: number;
+    retryCount: number;
+    lastActivity: number;
+    lastPromptSent: number;
+    lastResponse: string | null;
+    backlog: { totalIssues: number; completedIssues: number } | null;
+    uiState: UIStateResult | null;
+}
 
-            // DEBUG: Log what we received
-            console.log(`[${instance.projectName}] UI State: chatButtonColor=${uiState.chatButtonColor}, hasEnterButton=${uiState.hasEnterButton}, hasAcceptButton=${uiState.hasAcceptButton}`);
+function toSupervised(instance: Instance): SupervisedInstance {
+    return {
+        windowHandle: instance.windowHandle,
+        projectName: instance.projectName,
+        projectPath: instance.projectPath,
+        customPrompt: instance.customPrompt || null,
+        backlogPath: instance.backlogConfig?.path || null,
+        backlogMode: instance.backlogConfig?.mode || null,
+        enabled: instance.enabled && !instance.isBlocked
+    };
+}
 
-            // ========== STEP 1: Accept all (priority - immediate action) ==========
-            if (uiState.hasAcceptButton && uiState.isBottomButton) {
-                console.log(`[${instance.projectName}] Clicking Accept all at (${uiState.acceptButtonX}, ${uiState.acceptButtonY})`);
-                const acceptResult = await clickAcceptButton(instance.windowHandle, uiState.acceptButtonX, uiState.acceptButtonY);
-                console.log(`[${instance.projectName}] Accept all result: ${acceptResult}`);
-                instances.update(list =>
-                    list.map(i => i.id === instance.id
-                        ? { ...i, lastActivity: Date.now(), retryCount: 0 }
-                        : i
-                    )
-                );
-                continue;
+// Apply a supervisor decision to the instance card
+function applySupervisorUpdate(update: SupervisorUpdate): void {
+    console.log(`[${update.projectName}] ${update.message}`);
+    instances.update(list =>
+        list.map(i => i.windowHandle === update.windowHandle
+            ? {
+                ...i,
+                status: update.status,
+                stateReason: update.blockReason ?? i.stateReason,
+                enabled: update.resumed || (update.enabled && i.enabled),
+                isBlocked: update.resumed ? false : update.blockReason ? true : i.isBlocked,
+                blockReason: update.resumed ? undefined : update.blockReason ?? i.blockReason,
+                stepCount: update.stepCount,
+                retryCount: update.retryCount,
+                lastActivity: update.lastActivity || i.lastActivity,
+                lastPromptSent: update.lastPromptSent || i.lastPromptSent,
+                lastResponse: update.lastResponse ?? i.lastResponse,
+                totalIssues: update.backlog?.totalIssues ?? i.totalIssues,
+                currentIssue: update.backlog?.completedIssues ?? i.currentIssue,
+                issuesCompleted: update.backlog?.completedIssues ?? i.issuesCompleted
             }
+            : i
+        )
+    );
+}
 
-            // ========== SAFETY: isPaused overrides chatButtonColor ==========
-            // If isPaused is true (red square detected), agent is working even if
-            // chatButtonColor is "gray" (dialog may cover the corner red button)
-            if (uiState.isPaused && uiState.chatButtonColor !== "red") {
-                console.log(`[${instance.projectName}] isPaused=true but chatButtonColor=${uiState.chatButtonColor} - treating as working`);
-                // Check for Accept dialog (Run command?, etc.)
-                if (uiState.hasAcceptButton && !uiState.isBottomButton) {
-                    console.log(`[${instance.projectName}] Found Accept dialog while paused, sending Alt+Enter`);
-                    const acceptResult = await acceptDialog(instance.windowHandle);
-                    console.log(`[${instance.projectName}] Accept dialog result: ${acceptResult}`);
-                    instances.update(list =>
-                        list.map(i => i.id === instance.id
-                            ? { ...i, lastActivity: Date.now(), retryCount: 0 }
-                            : i
-                        )
-                    );
-                } else {
-                    console.log(`[${instance.projectName}] Agent working (isPaused) - waiting...`);
-                }
-                continue;
-            }
+// The polling loop runs in the backend supervisor so it keeps going while the
+// window is hidden; the webview only listens for its decisions
+let supervisorUnlisten: UnlistenFn | null = null;
 
-            // ========== SAFETY: chatButtonColor="none" = unreliable detection ==========
-            // On non-primary monitors, GetPixel can't read pixels - detection returns "none"
-            // Do NOT send prompts - we can't tell if agent is working or idle
-            if (uiState.chatButtonColor === "none") {
-                console.warn(`[${instance.projectName}] Pixel detection unreliable (non-primary monitor?) - skipping. Error: ${uiState.error || 'unknown'}`);
-                continue;
-            }
+export async function startAutoImplementation(): Promise<void> {
+    if (supervisorUnlisten) {
+        console.log('Supervisor already active');
+        return;
+    }
+    supervisorUnlisten = await listen<SupervisorUpdate>('supervisor-update', event =>
+        applySupervisorUpdate(event.payload)
+    );
 
-            // ========== STEP 2: Check chat button color ==========
-            if (uiState.chatButtonColor === "gray") {
-                // GRAY = Chat is ready for input
-
-                // First check for Retry button
-                if (uiState.hasRetryButton) {
-                    // Read maxRetries from current settings (not instance copy)
-                    const maxRetries = currentSettings.maxRetries;
-                    if (instance.retryCount >= maxRetries) {
-                        // Max retries reached - notify Discord and disable
-                        instances.update(list =>
-                            list.map(i => i.id === instance.id
-                                ? { ...i, isBlocked: true, blockReason: 'Max retries reached', status: 'error' as const, enabled: false }
-                                : i
-                            )
-                        );
-                        await notifyStopCondition(instance, 'Max retries reached');
-                        console.log(`[${instance.projectName}] Max retries (${maxRetries}) reached - Discord notified`);
-                    } else {
-                        // Click retry and increment counter
-                        console.log(`[${instance.projectName}] Attempting click at Retry coordinates: (${uiState.retryButtonX}, ${uiState.retryButtonY})`);
-                        const clickResult = await clickRetryButton(instance.windowHandle, uiState.retryButtonX, uiState.retryButtonY);
-                        console.log(`[${instance.projectName}] Click result: ${clickResult}`);
-                        instances.update(list =>
-                            list.map(i => i.id === instance.id
-                                ? { ...i, retryCount: i.retryCount + 1, lastActivity: Date.now() }
-                                : i
-                            )
-                        );
-                        console.log(`[${instance.projectName}] Clicked Retry (${instance.retryCount + 1}/${maxRetries})`);
-                    }
-                    continue;
-                }
+    // Update backlog info immediately when starting
+    updateInstanceBacklogs();
 
-                // No Retry - send prompt (hasEnterButton should be true)
-                if (uiState.hasEnterButton) {
-                    const prompt = instance.customPrompt || currentSettings.autoPrompt;
-                    console.log(`[${instance.projectName}] Chat ready - sending prompt: "${prompt.substring(0, 50)}..."`);
-
-                    const writeResult = await writeToChat(instance.windowHandle, prompt);
-                    console.log(`[${instance.projectName}] writeToChat result: ${writeResult}`);
-
-                    instances.update(list =>
-                        list.map(i => i.id === instance.id
-                            ? {
-                                ...i,
-                                lastActivity: Date.now(),
-                                lastPromptSent: Date.now(),  // Track when prompt was sent
-                                stepCount: i.stepCount + 1,
-                                retryCount: 0,
-                                status: 'working' as const
-                            }
-                            : i
-                        )
-                    );
-                }
-                continue;
-            }
+    try {
+        await invoke('start_supervisor', { instances: get(instances).map(toSupervised) });
+    } catch (error) {
+        console.error('Failed to start supervisor:', error);
+    }
+}
 
-            // ========== STEP 3: Red button = Agent working, may have Accept dialog ==========
-            if (uiState.chatButtonColor === "red") {
-                // Check if there's an Accept dialog (Run command?, etc.)
-                if (uiState.hasAcceptButton && !uiState.isBottomButton) {
-                    console.log(`[${instance.projectName}] Agent working - found Accept dialog, sending Alt+Enter`);
-                    const acceptResult = await acceptDialog(instance.windowHandle);
-                    console.log(`[${instance.projectName}] Accept dialog result: ${acceptResult}`);
-                    instances.update(list =>
-                        list.map(i => i.id === instance.id
-                            ? { ...i, lastActivity: Date.now(), retryCount: 0 }
-                            : i
-                        )
-                    );
-                } else {
-                    // Agent is working, nothing to do
-                    console.log(`[${instance.projectName}] Agent working (red button) - waiting...`);
-                }
-                continue;
-            }
+// Stop auto-implementation
+export async function stopAutoImplementation(): Promise<void> {
+    try {
+        await invoke('stop_supervisor');
+    } catch (error) {
+        console.error('Failed to stop supervisor:', error);
+    }
+    detachSupervisor();
+    console.log('Auto-implementation stopped');
+}
 
-            // ========== STEP 4: Accept dialog detected but chatButtonColor unknown ==========
-            // This can happen when the dialog appears but we couldn't sample the chat button
-            if (uiState.hasAcceptButton && !uiState.isBottomButton) {
-                console.log(`[${instance.projectName}] Found Accept dialog (color unknown), sending Alt+Enter`);
-                const acceptResult = await acceptDialog(instance.windowHandle);
-                console.log(`[${instance.projectName}] Accept dialog result: ${acceptResult}`);
-                instances.update(list =>
-                    list.map(i => i.id === instance.id
-                        ? { ...i, lastActivity: Date.now(), retryCount: 0 }
-                        : i
-                    )
-                );
-                continue;
-            }
+// Stop listening without stopping the backend loop (webview going away)
+export function detachSupervisor(): void {
+    supervisorUnlisten?.();
+    supervisorUnlisten = null;
+}
 
-            // ========== Unknown state - try to scroll to bottom ==========
-            // This can happen when scroll is stuck at top and we can't see the buttons
-            console.log(`[${instance.projectName}] Unknown state - scrolling to bottom (chatButtonColor: ${uiState.chatButtonColor})`);
-            await scrollToBottom(instance.windowHandle);
+export async function isSupervisorRunning(): Promise<boolean> {
+    try {
+        const state = await invoke<{ running: boolean }>('get_supervisor_status');
+        return state.running;
+    } catch {
+        return false;
+    }
+}
 
-        } catch (error) {
-            if (error instanceof Error && error.message === 'Timeout') {
-                console.warn(`[${instance.projectName}] Detection timeout - skipping`);
-            } else {
-                console.error(`[${instance.projectName}] Error during polling:`, error);
+// Enable or disable an instance: records the lifecycle transition and updates
+// a running supervisor. Re-enabling clears a previous block.
+export async function setInstanceEnabled(instanceId: string, enabled: boolean): Promise<void> {
+    const instance = get(instances).find(i => i.id === instanceId);
+    if (!instance) return;
+    instances.update(list =>
+        list.map(i => i.id === instanceId
+            ? {
+                ...i,
+                enabled,
+                isBlocked: enabled ? false : i.isBlocked,
+                blockReason: enabled ? undefined : i.blockReason
             }
-        }
+            : i
+        )
+    );
+    try {
+        const status = await invoke<Instance['status']>('set_instance_enabled', {
+            windowHandle: instance.windowHandle,
+            projectName: instance.projectName,
+            enabled
+        });
+        instances.update(list => list.map(i => i.id === instanceId ? { ...i, status } : i));
+    } catch (error) {
+        console.error('Failed to set instance state:', error);
     }
+    await updateSupervisedInstance(instanceId);
 }
 
-function scheduleNextPoll(intervalMs: number): void {
-    if (!pollingActive) return;
-
-    pollingTimeout = setTimeout(async () => {
-        if (!pollingActive) return;
+// Lifecycle transitions recorded for an instance
+export async function getStateHistory(windowHandle: number): Promise<StateHistory | null> {
+    try {
+        return await invoke<StateHistory>('get_state_history', { windowHandle });
+    } catch (error) {
+        console.error('Failed to get state history:', error);
+        return null;
+    }
+}
 
-        try {
-            await pollOnce();
-        } catch (error) {
-            console.error('Poll cycle error:', error);
-        }
+// Configured stop rules (the stopConditions setting is applied on top)
+export async function getRules(): Promise<Rule[]> {
+    try {
+        return await invoke<Rule[]>('get_rules');
+    } catch (error) {
+        console.error('Failed to get rules:', error);
+        return [];
+    }
+}
 
-        // Schedule next poll
-        scheduleNextPoll(intervalMs);
-    }, intervalMs);
+// Replace the configured rules; rejects with the validation error
+export async function saveRules(rules: Rule[]): Promise<void> {
+    await invoke('save_rules', { rules });
 }
 
-export function startAutoImplementation(intervalMs?: number): void {
-    const currentSettings = get(settings);
-    const pollInterval = intervalMs || (currentSettings.pollIntervalSeconds * 1000);
+// Rules that fired on an instance
+export async function getRuleFirings(windowHandle: number): Promise<RuleFiring[]> {
+    try {
+        return await invoke<RuleFiring[]>('get_rule_firings', { windowHandle });
+    } catch (error) {
+        console.error('Failed to get rule firings:', error);
+        return [];
+    }
+}
 
-    if (pollingActive) {
-        console.log('Polling already active');
-        return;
+// Budgets for an instance, or the global ones when projectName is omitted
+export async function getBudgetPolicy(projectName?: string): Promise<BudgetPolicy | null> {
+    try {
+        return await invoke<BudgetPolicy>('get_budget_policy', { projectName: projectName ?? null });
+    } catch (error) {
+        console.error('Failed to load budget policy:', error);
+        return null;
     }
+}
 
-    pollingActive = true;
-    console.log(`Starting auto-implementation polling every ${pollInterval}ms`);
+// Set the global budgets, or an instance override when projectName is given
+export async function setBudgetPolicy(policy: BudgetPolicy, projectName?: string): Promise<void> {
+    await invoke('set_budget_policy', { projectName: projectName ?? null, policy });
+}
 
-    // Update backlog info immediately when starting
-    updateInstanceBacklogs();
+export async function getPlaybooks(): Promise<Playbook[]> {
+    try {
+        return await invoke<Playbook[]>('get_playbooks');
+    } catch (error) {
+        console.error('Failed to get playbooks:', error);
+        return [];
+    }
+}
 
-    // Setup periodic backlog refresh (every 15 minutes)
-    const backlogRefreshInterval = setInterval(() => {
-        if (pollingActive) {
-            console.log('[Backlog] Periodic refresh...');
-            updateInstanceBacklogs();
-        } else {
-            clearInterval(backlogRefreshInterval);
-        }
-    }, 15 * 60 * 1000); // 15 minutes
-
-    // Start first poll immediately, then schedule subsequent ones
-    pollOnce().then(() => {
-        scheduleNextPoll(pollInterval);
-    }).catch(error => {
-        console.error('Initial poll error:', error);
-        scheduleNextPoll(pollInterval);
-    });
+// Replace the configured playbooks; rejects with the validation error
+export async function savePlaybooks(playbooks: Playbook[]): Promise<void> {
+    await invoke('save_playbooks', { playbooks });
 }
 
-// Stop auto-implementation
-export function stopAutoImplementation(): void {
-    pollingActive = false;
-    if (pollingTimeout) {
-        clearTimeout(pollingTimeout);
-        pollingTimeout = null;
-    }
-    if (pollingInterval) {
-        clearInterval(pollingInterval);
-        pollingInterval = null;
+// Playbook an instance uses, or the default one when projectName is omitted
+export async function getInstancePlaybook(projectName?: string): Promise<string | null> {
+    try {
+        return await invoke<string | null>('get_instance_playbook', { projectName: projectName ?? null });
+    } catch (error) {
+        console.error('Failed to get playbook:', error);
+        return null;
     }
-    console.log('Auto-implementation stopped');
 }
 
+// Assign a playbook to an instance, or the default one when projectName is omitted (null = none)
+export async function setInstancePlaybook(playbook: string | null, projectName?: string): Promise<void> {
+    await invoke('set_instance_playbook', { projectName: projectName ?? null, playbook });
+}
+
+// Push an instance change (enabled, prompt, backlog config) to a running supervisor
+export async function updateSupervisedInstance(instanceId: string): Promise<void> {
+    if (!supervisorUnlisten) return;
+    const instance = get(instances).find(i => i.id === instanceId);
+    if (!instance) return;
+    await invoke('update_supervised_instance', { instance: toSupervised(instance) })
+        .catch(error => console.error('Failed to update supervised instance:', error));
+}
@@ -1,6 +1,6 @@
 // Types for Antigravity Monitor
 
-export type InstanceStatus = 'idle' | 'working' | 'error' | 'complete' | 'disabled' | 'blocked';
+export type InstanceStatus = 'idle' | 'working' | 'error' | 'complete' | 'disabled' | 'blocked' | 'paused' | 'timedOut' | 'offHours' | 'stalled' | 'waiting';
 
 export type BacklogMode = 'auto' | 'file' | 'folder';
 
@@ -22,6 +22,7 @@ export interface Instance {
     retryCount: number;
     maxRetries: number;
     status: InstanceStatus;
+    stateReason?: string | null;  // Why the backend state machine entered the current status
     lastActivity: number;
     stepCount: number;
     // New fields for auto-implementation
@@ -29,7 +30,19 @@ export interface Instance {
     isBlocked?: boolean;
     blockReason?: string;
     issuesCompleted?: number;
+    currentIssueTitle?: string | null;  // Issue in progress, from the backend's last backlog read
+    lastRuleFiring?: RuleFiring | null;  // Most recent stop rule that fired
+    nextRetryAt?: number;  // When the backed-off Retry click is due (0 = none)
+    retryCooldownUntil?: number;  // When an instance out of retries resumes (0 = not cooling down)
+    playbook?: PlaybookProgress | null;  // Playbook step the backend supervisor is on
+    budget?: BudgetUsage;  // Prompts and time counted against the budgets
+    budgetPolicy?: BudgetPolicy;  // Budgets that apply to the instance
+    nextRunAt?: number;  // When an instance outside its schedule runs next (0 = not waiting on it)
     lastPromptSent?: number;  // Timestamp of last prompt sent (for inactivity timeout)
+    lastVisualChange?: number;  // Last time the chat area visibly changed (stall detection)
+    queueDepth?: number;  // Actions waiting in the global action queue
+    currentAction?: string | null;  // Action currently holding the queue for this window
+    dryRun?: boolean;  // Input and notifications are only logged (global or per-instance)
     // Backlog configuration
     backlogConfig?: BacklogConfig;
 }
@@ -46,7 +59,18 @@ export interface Settings {
     autoPrompt: string;
     pollIntervalSeconds: number;
     stopConditions: string[];
-    inactivityTimeoutMinutes: number;  // Minutes before stopping inactive project (default 20)
+    inactivityTimeoutMinutes: number;  // Minutes without activity before timing out a project (0 = off)
+    inactivityRecoveryPrompt: string;  // Sent once before timing out (empty = stop right away)
+    // Stall detection (agent "working" but chat not changing)
+    stallTimeoutMinutes: number;  // Minutes without visual change before marking stalled (0 = off)
+    notifyOnStall: boolean;
+    userIdleSeconds: number;  // Seconds without keyboard/mouse input before BOB may take focus (0 = off)
+    actionTimeoutSeconds: number;  // Max seconds an action may wait in the queue, and then run
+    dryRun: boolean;  // Log clicks, keys, pastes and notifications instead of performing them
+    // Context reset (new chat seeded with a carry-over summary)
+    newChatAfterSteps: number;  // Prompts in one chat before opening a new one (0 = off)
+    newChatOnDegradation: boolean;  // Also reset when the agent mentions context overflow
+    degradationPhrases: string[];
     // Logging settings
     loggingEnabled: boolean;
     logFilePath: string;  // Path to log file (e.g., "C:/logs/antigravity.log")
@@ -64,3 +88,197 @@ export interface StopCondition {
     condition: string;
     message: string;
 }
+
+// Result of an automation action (click, Alt+Enter, scroll, write to chat)
+export type ActionStage = 'started' | 'focused' | 'positioned' | 'inputSent' | 'done';
+
+export interface ActionOutcome {
+    action: string;
+    success: boolean;
+    stage: ActionStage;
+    elapsedMs: number;
+    error?: string;
+    focusObtained: boolean;
+    x?: number;
+    y?: number;
+    verified?: boolean;  // Re-detection confirmed the expected UI change
+    attempts: number;
+    dryRun: boolean;  // Steps were only logged
+    planned: PlannedInput[];  // Steps a dry run would have sent
+}
+
+// One input step recorded by a dry run
+export type PlannedInput =
+    | { type: 'focus'; windowHandle: number }
+    | { type: 'moveTo'; x: number; y: number }
+    | { type: 'click'; x: number; y: number }
+    | { type: 'wheel'; delta: number }
+    | { type: 'chord'; keys: number[] }
+    | { type: 'type'; text: string }
+    | { type: 'paste'; text: string };
+
+// Layout profiles (fractions of the window size)
+export interface LayoutPoint {
+    x: number;
+    y: number;
+}
+
+export interface LayoutRegion {
+    left: number;
+    top: number;
+    right: number;
+    bottom: number;
+}
+
+export interface LayoutProfile {
+    name: string;
+    description: string;
+    acceptRegion: LayoutRegion;
+    bottomButtonThreshold: number;
+    pauseRegion: LayoutRegion;
+    retryRegion: LayoutRegion;
+    chatProbe: { anchor: LayoutPoint; xOffsets: number[]; yOffsets: number[] };
+    scrollPoint: LayoutPoint;
+    chatInput?: LayoutPoint | null;
+    sendButton?: LayoutPoint | null;
+}
+
+export type CalibrationTarget = 'chatInput' | 'sendButton';
+
+// Keybinding profiles: key chords written as "Ctrl+Shift+L"
+export interface KeybindingProfile {
+    name: string;
+    description: string;
+    focusChat?: string | null;  // null = click the chat input instead
+    submit: string;
+    acceptSuggestion: string;  // Picks the highlighted slash-command popup entry
+    accept: string;
+    reject?: string | null;
+    scrollBottom: string;
+    newChat?: string | null;
+}
+
+// Backend lifecycle state machine (lifecycle.rs)
+export interface StateTransition {
+    from: InstanceStatus;
+    to: InstanceStatus;
+    reason: string;
+    at: number;
+}
+
+export interface StateHistory {
+    state: InstanceStatus;
+    since: number;
+    history: StateTransition[];
+}
+
+// Stop-condition rules (rules.rs)
+export type RuleScope = { type: 'global' } | { type: 'instance'; projectName: string };
+
+export type BacklogPredicate =
+    | { type: 'allCompleted' }
+    | { type: 'completedAtLeast'; count: number }
+    | { type: 'currentIssueContains'; text: string };
+
+export type RuleMatcher =
+    | { type: 'substring'; text: string; caseSensitive?: boolean }
+    | { type: 'regex'; pattern: string; caseSensitive?: boolean }
+    | { type: 'backlog'; predicate: BacklogPredicate };
+
+export type RuleAction =
+    | { type: 'pause' }
+    | { type: 'block'; reason?: string | null }
+    | { type: 'complete' }
+    | { type: 'notify'; title?: string | null; message?: string | null }
+    | { type: 'reply'; text: string }
+    | { type: 'hook'; command: string };
+
+export interface Rule {
+    name: string;
+    enabled?: boolean;
+    scope?: RuleScope;
+    matcher: RuleMatcher;
+    actions: RuleAction[];
+}
+
+export interface RuleFiring {
+    rule: string;
+    matched: string;  // Response line or backlog state that matched
+    actions: RuleAction[];
+    at: number;
+}
+
+// Retry backoff and cooldown, global or per instance
+export interface RetryPolicy {
+    initialDelayMs: number;
+    multiplier: number;
+    maxDelayMs: number;
+    jitter: number;  // Random spread as a fraction of each wait (0.2 = ±20%)
+    maxAttempts: number | null;  // null = the global maxRetries setting
+    windowMinutes: number;  // 0 = count since the last successful step
+    cooldownMinutes: number;  // 0 = stay disabled after giving up
+}
+
+// Run windows and quiet hours, global or per instance (both apply)
+export interface Schedule {
+    runWindows: string[];  // Cron expressions for the minutes it may run (empty = always)
+    quietHours: string[];  // "[days] HH:MM-HH:MM", e.g. "Mon-Fri 09:00-10:30"
+}
+
+// A prompt template rendered against a live instance
+export interface PromptPreview {
+    text: string;
+    unknown: string[];  // Variables used that don't exist (left as written)
+    variables: Record<string, string>;
+}
+
+// Multi-step playbooks (ordered prompts per issue)
+export type AdvanceCondition =
+    | { type: 'agentIdle' }
+    | { type: 'backlogChanged' }
+    | { type: 'stopPhrase'; text: string };
+
+export interface PlaybookStep {
+    name: string;
+    prompt: string;  // Template, rendered like the auto prompt
+    advance: AdvanceCondition;
+}
+
+export interface Playbook {
+    name: string;
+    steps: PlaybookStep[];
+}
+
+export interface PlaybookProgress {
+    playbook: string;
+    step: number;  // 0-based
+    stepName: string;
+    totalSteps: number;
+    issue: string;
+    stepStartedAt: number;
+}
+
+// Per-instance budgets (limit 0 = unlimited)
+export type OverBudgetAction = 'pause' | 'skipIssue' | 'notify';
+
+export interface BudgetLimit {
+    limit: number;
+    action: OverBudgetAction;
+}
+
+export interface BudgetPolicy {
+    promptsPerHour: BudgetLimit;
+    promptsPerDay: BudgetLimit;
+    stepsPerIssue: BudgetLimit;
+    minutesPerIssue: BudgetLimit;
+    skipPrompt: string;  // Template sent to make the agent skip an issue
+}
+
+export interface BudgetUsage {
+    promptsLastHour: number;
+    promptsLastDay: number;
+    issue: string;
+    issueStartedAt: number;
+    issueSteps: number;
+    skipped: string[];
+}